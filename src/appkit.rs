@@ -9,12 +9,14 @@
 
 #![allow(non_upper_case_globals)]
 
-use base::{id, class, BOOL, SEL};
+use base::{id, BOOL, SEL};
 use foundation::{NSInteger, NSUInteger, NSTimeInterval,
-                 NSPoint, NSSize, NSRect, NSRectEdge};
+                 NSPoint, NSSize, NSRect, NSRectEdge, NSObjectRef};
 use libc;
+use object::IsKindOf;
 
-pub use core_graphics::base::CGFloat;
+pub use foundation::CGFloat;
+#[cfg(target_os = "macos")]
 pub use core_graphics::geometry::CGPoint;
 
 pub use self::NSApplicationActivationPolicy::*;
@@ -29,7 +31,7 @@ pub type CGLContextObj = *mut libc::c_void;
 
 pub type GLint = libc::int32_t;
 
-#[link(name = "AppKit", kind = "framework")]
+#[cfg_attr(target_os = "macos", link(name = "AppKit", kind = "framework"))]
 extern {
     pub static NSAppKitVersionNumber: f64;
 }
@@ -61,30 +63,34 @@ pub const NSAppKitVersionNumber10_8: f64 = 1187.0;
 pub const NSAppKitVersionNumber10_9: f64 = 1265.0;
 
 pub unsafe fn NSApp() -> id {
-    msg_send![class("NSApplication"), sharedApplication]
+    msg_send![class!(NSApplication), sharedApplication]
 }
 
-#[repr(i64)]
+#[cfg_attr(target_pointer_width = "32", repr(i32))]
+#[cfg_attr(target_pointer_width = "64", repr(i64))]
 pub enum NSApplicationActivationPolicy {
     NSApplicationActivationPolicyRegular = 0,
     NSApplicationActivationPolicyAccessory = 1,
     NSApplicationActivationPolicyERROR = -1
 }
 
-#[repr(u64)]
+#[cfg_attr(target_pointer_width = "32", repr(u32))]
+#[cfg_attr(target_pointer_width = "64", repr(u64))]
 pub enum NSApplicationActivationOptions {
     NSApplicationActivateAllWindows = 1 << 0,
     NSApplicationActivateIgnoringOtherApps = 1 << 1
 }
 
-#[repr(u64)]
+#[cfg_attr(target_pointer_width = "32", repr(u32))]
+#[cfg_attr(target_pointer_width = "64", repr(u64))]
 pub enum NSApplicationTerminateReply {
     NSTerminateCancel = 0,
     NSTerminateNow = 1,
     NSTerminateLater = 2,
 }
 
-#[repr(u64)]
+#[cfg_attr(target_pointer_width = "32", repr(u32))]
+#[cfg_attr(target_pointer_width = "64", repr(u64))]
 pub enum NSWindowMask {
     NSBorderlessWindowMask      = 0,
     NSTitledWindowMask          = 1 << 0,
@@ -101,19 +107,30 @@ pub enum NSWindowMask {
     NSFullSizeContentViewWindowMask = 1 << 15
 }
 
-#[repr(u64)]
+#[cfg_attr(target_pointer_width = "32", repr(i32))]
+#[cfg_attr(target_pointer_width = "64", repr(i64))]
 pub enum NSWindowTitleVisibility {
     NSWindowTitleVisible = 0,
     NSWindowTitleHidden = 1
 }
 
-#[repr(u64)]
+#[cfg_attr(target_pointer_width = "32", repr(u32))]
+#[cfg_attr(target_pointer_width = "64", repr(u64))]
 pub enum NSBackingStoreType {
     NSBackingStoreRetained      = 0,
     NSBackingStoreNonretained   = 1,
     NSBackingStoreBuffered      = 2
 }
 
+encode_as! {
+    NSApplicationActivationPolicy => NSInteger,
+    NSApplicationActivationOptions => NSUInteger,
+    NSApplicationTerminateReply => NSUInteger,
+    NSWindowMask => NSUInteger,
+    NSWindowTitleVisibility => NSInteger,
+    NSBackingStoreType => NSUInteger,
+}
+
 bitflags! {
     flags NSWindowOrderingMode: NSInteger {
         const NSWindowAbove =  1,
@@ -122,6 +139,8 @@ bitflags! {
     }
 }
 
+encode_as!(NSWindowOrderingMode => NSInteger);
+
 bitflags! {
     flags NSAlignmentOptions: libc::c_ulonglong {
         const NSAlignMinXInward         = 1 << 0,
@@ -158,7 +177,120 @@ bitflags! {
     }
 }
 
-#[repr(u64)]
+encode_as!(NSAlignmentOptions => libc::c_ulonglong);
+
+#[derive(Copy, Clone)]
+enum Alignment {
+    Inward,
+    Outward,
+    Nearest,
+}
+
+/// Returns how `options` aligns one edge or size, or `None` if it aligns it in more than
+/// one way.
+fn alignment(options: NSAlignmentOptions,
+             inward: NSAlignmentOptions,
+             outward: NSAlignmentOptions,
+             nearest: NSAlignmentOptions) -> Option<Option<Alignment>> {
+    let mut alignment = None;
+    for &(flag, value) in &[(inward, Alignment::Inward),
+                            (outward, Alignment::Outward),
+                            (nearest, Alignment::Nearest)] {
+        if options.contains(flag) {
+            if alignment.is_some() {
+                return None;
+            }
+            alignment = Some(value);
+        }
+    }
+    Some(alignment)
+}
+
+/// Rounds to the nearest integer, breaking ties towards positive infinity, or towards
+/// negative infinity when `ties_down` is set.
+#[inline]
+fn round_nearest(value: CGFloat, ties_down: bool) -> CGFloat {
+    if ties_down { (value - 0.5).ceil() } else { (value + 0.5).floor() }
+}
+
+/// Aligns one axis of a rect in backing coordinates, returning its new origin and size,
+/// or `None` unless exactly two of the minimum, maximum and size are aligned.
+fn align_axis(min: CGFloat,
+              size: CGFloat,
+              min_alignment: Option<Alignment>,
+              max_alignment: Option<Alignment>,
+              size_alignment: Option<Alignment>,
+              ties_down: bool) -> Option<(CGFloat, CGFloat)> {
+    let align_min = |alignment| match alignment {
+        Alignment::Inward => min.ceil(),
+        Alignment::Outward => min.floor(),
+        Alignment::Nearest => round_nearest(min, ties_down),
+    };
+    let align_max = |alignment| match alignment {
+        Alignment::Inward => (min + size).floor(),
+        Alignment::Outward => (min + size).ceil(),
+        Alignment::Nearest => round_nearest(min + size, ties_down),
+    };
+    let align_size = |alignment| match alignment {
+        Alignment::Inward => size.floor(),
+        Alignment::Outward => size.ceil(),
+        Alignment::Nearest => round_nearest(size, false),
+    };
+    match (min_alignment, max_alignment, size_alignment) {
+        (Some(min), Some(max), None) => {
+            let min = align_min(min);
+            Some((min, align_max(max) - min))
+        }
+        (Some(min), None, Some(size)) => Some((align_min(min), align_size(size))),
+        (None, Some(max), Some(size)) => {
+            let size = align_size(size);
+            Some((align_max(max) - size, size))
+        }
+        _ => None,
+    }
+}
+
+/// Aligns `rect` to the pixel grid of a backing store with the given scale factor, the
+/// way `backingAlignedRect:options:` on `NSView`, `NSWindow` and `NSScreen` does.
+///
+/// `rect` is in points and so is the result. For each axis, `options` must specify how
+/// to align exactly two of the minimum edge, the maximum edge and the size, in exactly
+/// one way each; the third is derived from the other two. Inward alignment shrinks the
+/// rect and outward alignment grows it. Nearest alignment rounds halfway values up, or
+/// with `NSAlignRectFlipped` rounds halfway y coordinates down so that they move in the
+/// same direction on screen.
+///
+/// Returns `None` if `options` does not describe a valid alignment, which is where
+/// AppKit raises `NSInvalidArgumentException`, or if `scale_factor` is not a positive,
+/// finite number.
+pub fn backing_aligned_rect(rect: NSRect,
+                            scale_factor: CGFloat,
+                            options: NSAlignmentOptions) -> Option<NSRect> {
+    if scale_factor.is_nan() || scale_factor <= 0. || !scale_factor.is_finite() {
+        return None;
+    }
+    let flipped = options.contains(NSAlignRectFlipped);
+
+    let (x, width) = align_axis(
+        rect.origin.x * scale_factor,
+        rect.size.width * scale_factor,
+        alignment(options, NSAlignMinXInward, NSAlignMinXOutward, NSAlignMinXNearest)?,
+        alignment(options, NSAlignMaxXInward, NSAlignMaxXOutward, NSAlignMaxXNearest)?,
+        alignment(options, NSAlignWidthInward, NSAlignWidthOutward, NSAlignWidthNearest)?,
+        false)?;
+    let (y, height) = align_axis(
+        rect.origin.y * scale_factor,
+        rect.size.height * scale_factor,
+        alignment(options, NSAlignMinYInward, NSAlignMinYOutward, NSAlignMinYNearest)?,
+        alignment(options, NSAlignMaxYInward, NSAlignMaxYOutward, NSAlignMaxYNearest)?,
+        alignment(options, NSAlignHeightInward, NSAlignHeightOutward, NSAlignHeightNearest)?,
+        flipped)?;
+
+    Some(NSRect::new(NSPoint::new(x / scale_factor, y / scale_factor),
+                     NSSize::new(width / scale_factor, height / scale_factor)))
+}
+
+#[repr(u32)] // uint32_t
 pub enum NSOpenGLPixelFormatAttribute {
     NSOpenGLPFAAllRenderers             = 1,
     NSOpenGLPFATripleBuffer             = 3,
@@ -209,7 +341,8 @@ pub enum NSOpenGLPFAOpenGLProfiles {
     NSOpenGLProfileVersion4_1Core = 0x4100,
 }
 
-#[repr(u64)]
+#[cfg_attr(target_pointer_width = "32", repr(i32))]
+#[cfg_attr(target_pointer_width = "64", repr(i64))]
 pub enum NSOpenGLContextParameter {
     NSOpenGLCPSwapInterval          = 222,
     NSOpenGLCPSurfaceOrder          = 235,
@@ -223,7 +356,8 @@ pub enum NSOpenGLContextParameter {
     NSOpenGLCPMPSwapsInFlight       = 315,
 }
 
-#[repr(u64)]
+#[cfg_attr(target_pointer_width = "32", repr(u32))]
+#[cfg_attr(target_pointer_width = "64", repr(u64))]
 #[derive(Copy, Clone, PartialEq)]
 pub enum NSWindowButton {
     NSWindowCloseButton            = 0,
@@ -235,11 +369,26 @@ pub enum NSWindowButton {
     NSWindowFullScreenButton       = 7,
 }
 
+encode_as! {
+    NSOpenGLPixelFormatAttribute => u32,
+    NSOpenGLPFAOpenGLProfiles => u64,
+    NSOpenGLContextParameter => NSInteger,
+    NSWindowButton => NSUInteger,
+}
+
 pub static NSMainMenuWindowLevel: libc::int32_t = 24;
 
+object_ref! {
+    pub struct NSResponderRef(NSResponder): NSObjectRef;
+}
+
+object_ref! {
+    pub struct NSApplicationRef(NSApplication): NSResponderRef, NSObjectRef;
+}
+
 pub trait NSApplication {
     unsafe fn sharedApplication(_: Self) -> id {
-        msg_send![class("NSApplication"), sharedApplication]
+        msg_send![class!(NSApplication), sharedApplication]
     }
 
     unsafe fn setActivationPolicy_(self, policy: NSApplicationActivationPolicy) -> BOOL;
@@ -258,29 +407,29 @@ pub trait NSApplication {
     unsafe fn stop_(self, sender: id);
 }
 
-impl NSApplication for id {
+impl<T: IsKindOf<NSApplicationRef>> NSApplication for T {
     unsafe fn setActivationPolicy_(self, policy: NSApplicationActivationPolicy) -> BOOL {
-        msg_send![self, setActivationPolicy:policy as NSInteger]
+        msg_send![self.as_id(), setActivationPolicy:policy as NSInteger]
     }
 
     unsafe fn setMainMenu_(self, menu: id) {
-        msg_send![self, setMainMenu:menu]
+        msg_send![self.as_id(), setMainMenu:menu]
     }
 
     unsafe fn setServicesMenu_(self, menu: id) {
-        msg_send![self, setServicesMenu:menu]
+        msg_send![self.as_id(), setServicesMenu:menu]
     }
 
     unsafe fn activateIgnoringOtherApps_(self, ignore: BOOL) {
-        msg_send![self, activateIgnoringOtherApps:ignore]
+        msg_send![self.as_id(), activateIgnoringOtherApps:ignore]
     }
 
     unsafe fn run(self) {
-        msg_send![self, run]
+        msg_send![self.as_id(), run]
     }
 
     unsafe fn finishLaunching(self) {
-        msg_send![self, finishLaunching]
+        msg_send![self.as_id(), finishLaunching]
     }
 
     unsafe fn nextEventMatchingMask_untilDate_inMode_dequeue_(self,
@@ -288,41 +437,49 @@ impl NSApplication for id {
                                                               expiration: id,
                                                               in_mode: id,
                                                               dequeue: BOOL) -> id {
-        msg_send![self, nextEventMatchingMask:mask
-                                    untilDate:expiration
-                                       inMode:in_mode
-                                      dequeue:dequeue]
+        msg_send![self.as_id(), nextEventMatchingMask:mask
+                                            untilDate:expiration
+                                               inMode:in_mode
+                                              dequeue:dequeue]
     }
 
     unsafe fn sendEvent_(self, an_event: id) {
-        msg_send![self, sendEvent:an_event]
+        msg_send![self.as_id(), sendEvent:an_event]
     }
 
     unsafe fn postEvent_atStart_(self, anEvent: id, flag: BOOL) {
-        msg_send![self, postEvent:anEvent atStart:flag]
+        msg_send![self.as_id(), postEvent:anEvent atStart:flag]
     }
 
     unsafe fn stop_(self, sender: id) {
-        msg_send![self, stop:sender]
+        msg_send![self.as_id(), stop:sender]
     }
 }
 
+object_ref! {
+    pub struct NSRunningApplicationRef(NSRunningApplication): NSObjectRef;
+}
+
 pub trait NSRunningApplication {
     unsafe fn currentApplication(_: Self) -> id {
-        msg_send![class("NSRunningApplication"), currentApplication]
+        msg_send![class!(NSRunningApplication), currentApplication]
     }
     unsafe fn activateWithOptions_(self, options: NSApplicationActivationOptions) -> BOOL;
 }
 
-impl NSRunningApplication for id {
+impl<T: IsKindOf<NSRunningApplicationRef>> NSRunningApplication for T {
     unsafe fn activateWithOptions_(self, options: NSApplicationActivationOptions) -> BOOL {
-        msg_send![self, activateWithOptions:options as NSUInteger]
+        msg_send![self.as_id(), activateWithOptions:options as NSUInteger]
     }
 }
 
+object_ref! {
+    pub struct NSMenuRef(NSMenu): NSObjectRef;
+}
+
 pub trait NSMenu {
     unsafe fn new(_: Self) -> id {
-        msg_send![class("NSMenu"), new]
+        msg_send![class!(NSMenu), new]
     }
 
     unsafe fn setAutoenablesItems(self, state: BOOL);
@@ -331,31 +488,35 @@ pub trait NSMenu {
     unsafe fn addItemWithTitle_action_keyEquivalent(self, title: id, action: SEL, key: id) -> id;
 }
 
-impl NSMenu for id {
+impl<T: IsKindOf<NSMenuRef>> NSMenu for T {
     unsafe fn setAutoenablesItems(self, state: BOOL) {
-        msg_send![self, setAutoenablesItems: state]
+        msg_send![self.as_id(), setAutoenablesItems: state]
     }
 
     unsafe fn addItem_(self, menu_item: id) {
-        msg_send![self, addItem:menu_item]
+        msg_send![self.as_id(), addItem:menu_item]
     }
 
     unsafe fn addItemWithTitle_action_keyEquivalent(self, title: id, action: SEL, key: id) -> id {
-        msg_send![self, addItemWithTitle:title action:action keyEquivalent:key]
+        msg_send![self.as_id(), addItemWithTitle:title action:action keyEquivalent:key]
     }
 }
 
+object_ref! {
+    pub struct NSMenuItemRef(NSMenuItem): NSObjectRef;
+}
+
 pub trait NSMenuItem {
     unsafe fn alloc(_: Self) -> id {
-        msg_send![class("NSMenuItem"), alloc]
+        msg_send![class!(NSMenuItem), alloc]
     }
 
     unsafe fn new(_: Self) -> id {
-        msg_send![class("NSMenuItem"), new]
+        msg_send![class!(NSMenuItem), new]
     }
 
     unsafe fn separatorItem(_: Self) -> id {
-        msg_send![class("NSMenuItem"), separatorItem]
+        msg_send![class!(NSMenuItem), separatorItem]
     }
 
     unsafe fn initWithTitle_action_keyEquivalent_(self, title: id, action: SEL, key: id) -> id;
@@ -363,17 +524,17 @@ pub trait NSMenuItem {
     unsafe fn setSubmenu_(self, submenu: id);
 }
 
-impl NSMenuItem for id {
+impl<T: IsKindOf<NSMenuItemRef>> NSMenuItem for T {
     unsafe fn initWithTitle_action_keyEquivalent_(self, title: id, action: SEL, key: id) -> id {
-        msg_send![self, initWithTitle:title action:action keyEquivalent:key]
+        msg_send![self.as_id(), initWithTitle:title action:action keyEquivalent:key]
     }
 
     unsafe fn setKeyEquivalentModifierMask_(self, mask: NSEventModifierFlags) {
-        msg_send![self, setKeyEquivalentModifierMask:mask]
+        msg_send![self.as_id(), setKeyEquivalentModifierMask:mask]
     }
 
     unsafe fn setSubmenu_(self, submenu: id) {
-        msg_send![self, setSubmenu:submenu]
+        msg_send![self.as_id(), setSubmenu:submenu]
     }
 }
 
@@ -403,9 +564,18 @@ bitflags! {
     }
 }
 
+encode_as! {
+    NSWindowCollectionBehavior => NSUInteger,
+    NSWindowOcclusionState => NSUInteger,
+}
+
+object_ref! {
+    pub struct NSWindowRef(NSWindow): NSResponderRef, NSObjectRef;
+}
+
 pub trait NSWindow {
     unsafe fn alloc(_: Self) -> id {
-        msg_send![class("NSWindow"), alloc]
+        msg_send![class!(NSWindow), alloc]
     }
 
     // Creating Windows
@@ -475,7 +645,7 @@ pub trait NSWindow {
     unsafe fn frame(self) -> NSRect;
     unsafe fn setFrameOrigin_(self, point: NSPoint);
     unsafe fn setFrameTopLeftPoint_(self, point: NSPoint);
-    unsafe fn constrainFrameRect_toScreen_(self, frameRect: NSRect, screen: id);
+    unsafe fn constrainFrameRect_toScreen_(self, frameRect: NSRect, screen: id) -> NSRect;
     unsafe fn cascadeTopLeftFromPoint_(self, topLeft: NSPoint) -> NSPoint;
     unsafe fn setFrame_display_(self, windowFrame: NSRect, display: BOOL);
     unsafe fn setFrame_displayViews_(self, windowFrame: NSRect, display: BOOL);
@@ -632,7 +802,7 @@ pub trait NSWindow {
     // TODO: Constraint-Based Layouts
 }
 
-impl NSWindow for id {
+impl<T: IsKindOf<NSWindowRef>> NSWindow for T {
     // Creating Windows
 
     unsafe fn initWithContentRect_styleMask_backing_defer_(self,
@@ -640,10 +810,10 @@ impl NSWindow for id {
                                                            style: NSUInteger,
                                                            backing: NSBackingStoreType,
                                                            defer: BOOL) -> id {
-        msg_send![self, initWithContentRect:rect
-                                  styleMask:style
-                                    backing:backing as NSUInteger
-                                      defer:defer]
+        msg_send![self.as_id(), initWithContentRect:rect
+                                          styleMask:style
+                                            backing:backing as NSUInteger
+                                              defer:defer]
     }
 
     unsafe fn initWithContentRect_styleMask_backing_defer_screen_(self,
@@ -652,136 +822,136 @@ impl NSWindow for id {
                                                                   backing: NSBackingStoreType,
                                                                   defer: BOOL,
                                                                   screen: id) -> id {
-        msg_send![self, initWithContentRect:rect
-                                  styleMask:style
-                                    backing:backing as NSUInteger
-                                      defer:defer
-                                     screen:screen]
+        msg_send![self.as_id(), initWithContentRect:rect
+                                          styleMask:style
+                                            backing:backing as NSUInteger
+                                              defer:defer
+                                             screen:screen]
     }
 
     // Configuring Windows
 
     unsafe fn styleMask(self) -> NSUInteger {
-        msg_send![self, styleMask]
+        msg_send![self.as_id(), styleMask]
     }
 
     unsafe fn setStyleMask_(self, styleMask: NSUInteger) {
-        msg_send![self, setStyleMask:styleMask]
+        msg_send![self.as_id(), setStyleMask:styleMask]
     }
 
     unsafe fn toggleFullScreen_(self, sender: id) {
-        msg_send![self, toggleFullScreen:sender]
+        msg_send![self.as_id(), toggleFullScreen:sender]
     }
 
     unsafe fn worksWhenModal(self) -> BOOL {
-        msg_send![self, worksWhenModal]
+        msg_send![self.as_id(), worksWhenModal]
     }
 
     unsafe fn alphaValue(self) -> CGFloat {
-        msg_send![self, alphaValue]
+        msg_send![self.as_id(), alphaValue]
     }
 
     unsafe fn setAlphaValue_(self, windowAlpha: CGFloat) {
-        msg_send![self, setAlphaValue:windowAlpha]
+        msg_send![self.as_id(), setAlphaValue:windowAlpha]
     }
 
     unsafe fn backgroundColor(self) -> id {
-        msg_send![self, backgroundColor]
+        msg_send![self.as_id(), backgroundColor]
     }
 
     unsafe fn setBackgroundColor_(self, color: id) {
-        msg_send![self, setBackgroundColor:color]
+        msg_send![self.as_id(), setBackgroundColor:color]
     }
 
     unsafe fn colorSpace(self) -> id {
-        msg_send![self, colorSpace]
+        msg_send![self.as_id(), colorSpace]
     }
 
     unsafe fn setColorSpace_(self, colorSpace: id) {
-        msg_send![self, setColorSpace:colorSpace]
+        msg_send![self.as_id(), setColorSpace:colorSpace]
     }
 
     unsafe fn contentView(self) -> id {
-        msg_send![self, contentView]
+        msg_send![self.as_id(), contentView]
     }
 
     unsafe fn setContentView_(self, view: id) {
-        msg_send![self, setContentView:view]
+        msg_send![self.as_id(), setContentView:view]
     }
 
     unsafe fn canHide(self) -> BOOL {
-        msg_send![self, canHide]
+        msg_send![self.as_id(), canHide]
     }
 
     unsafe fn setCanHide_(self, canHide: BOOL) {
-        msg_send![self, setCanHide:canHide]
+        msg_send![self.as_id(), setCanHide:canHide]
     }
 
     unsafe fn hidesOnDeactivate(self) -> BOOL {
-        msg_send![self, hidesOnDeactivate]
+        msg_send![self.as_id(), hidesOnDeactivate]
     }
 
     unsafe fn setHidesOnDeactivate_(self, hideOnDeactivate: BOOL) {
-        msg_send![self, setHidesOnDeactivate:hideOnDeactivate]
+        msg_send![self.as_id(), setHidesOnDeactivate:hideOnDeactivate]
     }
 
     unsafe fn collectionBehavior(self) -> NSWindowCollectionBehavior {
-        msg_send![self, collectionBehavior]
+        msg_send![self.as_id(), collectionBehavior]
     }
 
     unsafe fn setCollectionBehavior_(self, collectionBehavior: NSWindowCollectionBehavior) {
-        msg_send![self, setCollectionBehavior:collectionBehavior]
+        msg_send![self.as_id(), setCollectionBehavior:collectionBehavior]
     }
 
     unsafe fn setOpaque_(self, opaque: BOOL) {
-        msg_send![self, setOpaque:opaque]
+        msg_send![self.as_id(), setOpaque:opaque]
     }
 
     unsafe fn hasShadow(self) -> BOOL {
-        msg_send![self, hasShadow]
+        msg_send![self.as_id(), hasShadow]
     }
 
     unsafe fn setHasShadow_(self, hasShadow: BOOL) {
-        msg_send![self, setHasShadow:hasShadow]
+        msg_send![self.as_id(), setHasShadow:hasShadow]
     }
 
     unsafe fn invalidateShadow(self) {
-        msg_send![self, invalidateShadow]
+        msg_send![self.as_id(), invalidateShadow]
     }
 
     unsafe fn autorecalculatesContentBorderThicknessForEdge_(self, edge: NSRectEdge) -> BOOL {
-        msg_send![self, autorecalculatesContentBorderThicknessForEdge:edge]
+        msg_send![self.as_id(), autorecalculatesContentBorderThicknessForEdge:edge]
     }
 
     unsafe fn setAutorecalculatesContentBorderThickness_forEdge_(self,
                                                                  autorecalculateContentBorderThickness: BOOL,
                                                                  edge: NSRectEdge) -> BOOL {
-        msg_send![self, setAutorecalculatesContentBorderThickness:
-                        autorecalculateContentBorderThickness forEdge:edge]
+        msg_send![self.as_id(), setAutorecalculatesContentBorderThickness:
+                                autorecalculateContentBorderThickness forEdge:edge]
     }
 
     unsafe fn contentBorderThicknessForEdge_(self, edge: NSRectEdge) -> CGFloat {
-        msg_send![self, contentBorderThicknessForEdge:edge]
+        msg_send![self.as_id(), contentBorderThicknessForEdge:edge]
     }
 
     unsafe fn setContentBorderThickness_forEdge_(self, borderThickness: CGFloat, edge: NSRectEdge) {
-        msg_send![self, setContentBorderThickness:borderThickness forEdge:edge]
+        msg_send![self.as_id(), setContentBorderThickness:borderThickness forEdge:edge]
     }
 
     unsafe fn delegate(self) -> id {
-        msg_send![self, delegate]
+        msg_send![self.as_id(), delegate]
     }
 
     unsafe fn setDelegate_(self, delegate: id) {
-        msg_send![self, setDelegate:delegate]
+        msg_send![self.as_id(), setDelegate:delegate]
     }
 
     unsafe fn preventsApplicationTerminationWhenModal(self) -> BOOL {
-        msg_send![self, preventsApplicationTerminationWhenModal]
+        msg_send![self.as_id(), preventsApplicationTerminationWhenModal]
     }
 
     unsafe fn setPreventsApplicationTerminationWhenModal_(self, flag: BOOL) {
-        msg_send![self, setPreventsApplicationTerminationWhenModal:flag]
+        msg_send![self.as_id(), setPreventsApplicationTerminationWhenModal:flag]
     }
 
     // TODO: Accessing Window Information
@@ -789,37 +959,37 @@ impl NSWindow for id {
     // Getting Layout Information
 
     unsafe fn contentRectForFrameRect_styleMask_(self, windowFrame: NSRect, windowStyle: NSUInteger) -> NSRect {
-        msg_send![self, contentRectForFrameRect:windowFrame styleMask:windowStyle]
+        msg_send![self.as_id(), contentRectForFrameRect:windowFrame styleMask:windowStyle]
     }
 
     unsafe fn frameRectForContentRect_styleMask_(self, windowContentRect: NSRect, windowStyle: NSUInteger) -> NSRect {
-        msg_send![self, frameRectForContentRect:windowContentRect styleMask:windowStyle]
+        msg_send![self.as_id(), frameRectForContentRect:windowContentRect styleMask:windowStyle]
     }
 
     unsafe fn minFrameWidthWithTitle_styleMask_(self, windowTitle: id, windowStyle: NSUInteger) -> CGFloat {
-        msg_send![self, minFrameWidthWithTitle:windowTitle styleMask:windowStyle]
+        msg_send![self.as_id(), minFrameWidthWithTitle:windowTitle styleMask:windowStyle]
     }
 
     unsafe fn contentRectForFrameRect_(self, windowFrame: NSRect) -> NSRect {
-        msg_send![self, contentRectForFrameRect:windowFrame]
+        msg_send![self.as_id(), contentRectForFrameRect:windowFrame]
     }
 
     unsafe fn frameRectForContentRect_(self, windowContent: NSRect) -> NSRect {
-        msg_send![self, frameRectForContentRect:windowContent]
+        msg_send![self.as_id(), frameRectForContentRect:windowContent]
     }
 
     // Managing Windows
 
     unsafe fn drawers(self) -> id {
-        msg_send![self, drawers]
+        msg_send![self.as_id(), drawers]
     }
 
     unsafe fn windowController(self) -> id {
-        msg_send![self, windowController]
+        msg_send![self.as_id(), windowController]
     }
 
     unsafe fn setWindowController_(self, windowController: id) {
-        msg_send![self, setWindowController:windowController]
+        msg_send![self.as_id(), setWindowController:windowController]
     }
 
     // TODO: Managing Sheets
@@ -827,201 +997,201 @@ impl NSWindow for id {
     // Sizing Windows
 
     unsafe fn frame(self) -> NSRect {
-        msg_send![self, frame]
+        msg_send![self.as_id(), frame]
     }
 
     unsafe fn setFrameOrigin_(self, point: NSPoint) {
-        msg_send![self, setFrameOrigin:point]
+        msg_send![self.as_id(), setFrameOrigin:point]
     }
 
     unsafe fn setFrameTopLeftPoint_(self, point: NSPoint) {
-        msg_send![self, setFrameTopLeftPoint:point]
+        msg_send![self.as_id(), setFrameTopLeftPoint:point]
     }
 
-    unsafe fn constrainFrameRect_toScreen_(self, frameRect: NSRect, screen: id) {
-        msg_send![self, constrainFrameRect:frameRect toScreen:screen]
+    unsafe fn constrainFrameRect_toScreen_(self, frameRect: NSRect, screen: id) -> NSRect {
+        msg_send![self.as_id(), constrainFrameRect:frameRect toScreen:screen]
     }
 
     unsafe fn cascadeTopLeftFromPoint_(self, topLeft: NSPoint) -> NSPoint {
-        msg_send![self, cascadeTopLeftFromPoint:topLeft]
+        msg_send![self.as_id(), cascadeTopLeftFromPoint:topLeft]
     }
 
     unsafe fn setFrame_display_(self, windowFrame: NSRect, display: BOOL) {
-        msg_send![self, setFrame:windowFrame display:display]
+        msg_send![self.as_id(), setFrame:windowFrame display:display]
     }
 
     unsafe fn setFrame_displayViews_(self, windowFrame: NSRect, display: BOOL) {
-        msg_send![self, setFrame:windowFrame displayViews:display]
+        msg_send![self.as_id(), setFrame:windowFrame displayViews:display]
     }
 
     unsafe fn aspectRatio(self) -> NSSize {
-        msg_send![self, aspectRatio]
+        msg_send![self.as_id(), aspectRatio]
     }
 
     unsafe fn setAspectRatio_(self, aspectRatio: NSSize) {
-        msg_send![self, setAspectRatio:aspectRatio]
+        msg_send![self.as_id(), setAspectRatio:aspectRatio]
     }
 
     unsafe fn minSize(self) -> NSSize {
-        msg_send![self, minSize]
+        msg_send![self.as_id(), minSize]
     }
 
     unsafe fn setMinSize_(self, minSize: NSSize) {
-        msg_send![self, setMinSize:minSize]
+        msg_send![self.as_id(), setMinSize:minSize]
     }
 
     unsafe fn maxSize(self) -> NSSize {
-        msg_send![self, maxSize]
+        msg_send![self.as_id(), maxSize]
     }
 
     unsafe fn setMaxSize_(self, maxSize: NSSize) {
-        msg_send![self, setMaxSize:maxSize]
+        msg_send![self.as_id(), setMaxSize:maxSize]
     }
 
     unsafe fn performZoom_(self, sender: id) {
-        msg_send![self, performZoom:sender]
+        msg_send![self.as_id(), performZoom:sender]
     }
 
     unsafe fn zoom_(self, sender: id) {
-        msg_send![self, zoom:sender]
+        msg_send![self.as_id(), zoom:sender]
     }
 
     unsafe fn resizeFlags(self) -> NSInteger {
-        msg_send![self, resizeFlags]
+        msg_send![self.as_id(), resizeFlags]
     }
 
     unsafe fn showsResizeIndicator(self) -> BOOL {
-        msg_send![self, showsResizeIndicator]
+        msg_send![self.as_id(), showsResizeIndicator]
     }
 
     unsafe fn setShowsResizeIndicator_(self, showsResizeIndicator: BOOL) {
-        msg_send![self, setShowsResizeIndicator:showsResizeIndicator]
+        msg_send![self.as_id(), setShowsResizeIndicator:showsResizeIndicator]
     }
 
     unsafe fn resizeIncrements(self) -> NSSize {
-        msg_send![self, resizeIncrements]
+        msg_send![self.as_id(), resizeIncrements]
     }
 
     unsafe fn setResizeIncrements_(self, resizeIncrements: NSSize) {
-        msg_send![self, setResizeIncrements:resizeIncrements]
+        msg_send![self.as_id(), setResizeIncrements:resizeIncrements]
     }
 
     unsafe fn preservesContentDuringLiveResize(self) -> BOOL {
-        msg_send![self, preservesContentDuringLiveResize]
+        msg_send![self.as_id(), preservesContentDuringLiveResize]
     }
 
     unsafe fn setPreservesContentDuringLiveResize_(self, preservesContentDuringLiveResize: BOOL) {
-        msg_send![self, setPreservesContentDuringLiveResize:preservesContentDuringLiveResize]
+        msg_send![self.as_id(), setPreservesContentDuringLiveResize:preservesContentDuringLiveResize]
     }
 
     unsafe fn inLiveResize(self) -> BOOL {
-        msg_send![self, inLiveResize]
+        msg_send![self.as_id(), inLiveResize]
     }
 
     // Sizing Content
 
     unsafe fn contentAspectRatio(self) -> NSSize {
-        msg_send![self, contentAspectRatio]
+        msg_send![self.as_id(), contentAspectRatio]
     }
 
     unsafe fn setContentAspectRatio_(self, contentAspectRatio: NSSize) {
-        msg_send![self, setContentAspectRatio:contentAspectRatio]
+        msg_send![self.as_id(), setContentAspectRatio:contentAspectRatio]
     }
 
     unsafe fn contentMinSize(self) -> NSSize {
-        msg_send![self, contentMinSize]
+        msg_send![self.as_id(), contentMinSize]
     }
 
     unsafe fn setContentMinSize_(self, contentMinSize: NSSize) {
-        msg_send![self, setContentMinSize:contentMinSize]
+        msg_send![self.as_id(), setContentMinSize:contentMinSize]
     }
 
     unsafe fn contentSize(self) -> NSSize {
-        msg_send![self, contentSize]
+        msg_send![self.as_id(), contentSize]
     }
 
     unsafe fn setContentSize_(self, contentSize: NSSize) {
-        msg_send![self, setContentSize:contentSize]
+        msg_send![self.as_id(), setContentSize:contentSize]
     }
 
     unsafe fn contentMaxSize(self) -> NSSize {
-        msg_send![self, contentMaxSize]
+        msg_send![self.as_id(), contentMaxSize]
     }
 
     unsafe fn setContentMaxSize_(self, contentMaxSize: NSSize) {
-        msg_send![self, setContentMaxSize:contentMaxSize]
+        msg_send![self.as_id(), setContentMaxSize:contentMaxSize]
     }
 
     unsafe fn contentResizeIncrements(self) -> NSSize {
-        msg_send![self, contentResizeIncrements]
+        msg_send![self.as_id(), contentResizeIncrements]
     }
 
     unsafe fn setContentResizeIncrements_(self, contentResizeIncrements: NSSize) {
-        msg_send![self, setContentResizeIncrements:contentResizeIncrements]
+        msg_send![self.as_id(), setContentResizeIncrements:contentResizeIncrements]
     }
 
     // Managing Window Visibility and Occlusion State
 
     unsafe fn isVisible(self) -> BOOL {
-        msg_send![self, isVisible]
+        msg_send![self.as_id(), isVisible]
     }
 
     unsafe fn occlusionState(self) -> NSWindowOcclusionState {
-        msg_send![self, occlusionState]
+        msg_send![self.as_id(), occlusionState]
     }
 
     // Managing Window Layers
 
     unsafe fn orderOut_(self, sender: id) {
-        msg_send![self, orderOut:sender]
+        msg_send![self.as_id(), orderOut:sender]
     }
 
     unsafe fn orderBack_(self, sender: id) {
-        msg_send![self, orderBack:sender]
+        msg_send![self.as_id(), orderBack:sender]
     }
 
     unsafe fn orderFront_(self, sender: id) {
-        msg_send![self, orderFront:sender]
+        msg_send![self.as_id(), orderFront:sender]
     }
 
     unsafe fn orderFrontRegardless(self) {
-        msg_send![self, orderFrontRegardless]
+        msg_send![self.as_id(), orderFrontRegardless]
     }
 
     unsafe fn orderFrontWindow_relativeTo_(self, ordering_mode: NSWindowOrderingMode, other_window_number: NSInteger) {
-        msg_send![self, orderWindow:ordering_mode relativeTo:other_window_number]
+        msg_send![self.as_id(), orderWindow:ordering_mode relativeTo:other_window_number]
     }
 
     unsafe fn level(self) -> NSInteger {
-        msg_send![self, level]
+        msg_send![self.as_id(), level]
     }
 
     unsafe fn setLevel_(self, level: NSInteger) {
-        msg_send![self, setLevel:level]
+        msg_send![self.as_id(), setLevel:level]
     }
 
     // Managing Key Status
 
     unsafe fn canBecomeKeyWindow(self) -> BOOL {
-        msg_send![self, canBecomeKeyWindow]
+        msg_send![self.as_id(), canBecomeKeyWindow]
     }
 
     unsafe fn makeKeyWindow(self) {
-        msg_send![self, makeKeyWindow]
+        msg_send![self.as_id(), makeKeyWindow]
     }
 
     unsafe fn makeKeyAndOrderFront_(self, sender: id) {
-        msg_send![self, makeKeyAndOrderFront:sender]
+        msg_send![self.as_id(), makeKeyAndOrderFront:sender]
     }
 
     // Managing Main Status
 
     unsafe fn canBecomeMainWindow(self) -> BOOL {
-        msg_send![self, canBecomeMainWindow]
+        msg_send![self.as_id(), canBecomeMainWindow]
     }
 
     unsafe fn makeMainWindow(self) {
-        msg_send![self, makeMainWindow]
+        msg_send![self.as_id(), makeMainWindow]
     }
 
     // TODO: Managing Toolbars
@@ -1035,7 +1205,7 @@ impl NSWindow for id {
     // Managing Title Bars
 
     unsafe fn standardWindowButton_(self, windowButtonKind: NSWindowButton) -> id {
-        msg_send![self, standardWindowButton:windowButtonKind]
+        msg_send![self.as_id(), standardWindowButton:windowButtonKind]
     }
 
     // TODO: Managing Tooltips
@@ -1044,19 +1214,19 @@ impl NSWindow for id {
     // Managing Responders
 
     unsafe fn initialFirstResponder(self) -> id {
-        msg_send![self, initialFirstResponder]
+        msg_send![self.as_id(), initialFirstResponder]
     }
 
     unsafe fn firstResponder(self) -> id {
-        msg_send![self, firstResponder]
+        msg_send![self.as_id(), firstResponder]
     }
 
     unsafe fn setInitialFirstResponder_(self, responder: id) {
-        msg_send![self, setInitialFirstResponder:responder]
+        msg_send![self.as_id(), setInitialFirstResponder:responder]
     }
 
     unsafe fn makeFirstResponder_(self, responder: id) -> BOOL {
-        msg_send![self, makeFirstResponder:responder]
+        msg_send![self.as_id(), makeFirstResponder:responder]
     }
 
     // TODO: Managing the Key View Loop
@@ -1064,181 +1234,181 @@ impl NSWindow for id {
     // Handling Keyboard Events
 
     unsafe fn keyDown_(self, event: id) {
-        msg_send![self, keyDown:event]
+        msg_send![self.as_id(), keyDown:event]
     }
 
     // Handling Mouse Events
 
     unsafe fn acceptsMouseMovedEvents(self) -> BOOL {
-        msg_send![self, acceptsMouseMovedEvents]
+        msg_send![self.as_id(), acceptsMouseMovedEvents]
     }
 
     unsafe fn ignoresMouseEvents(self) -> BOOL {
-        msg_send![self, ignoresMouseEvents]
+        msg_send![self.as_id(), ignoresMouseEvents]
     }
 
     unsafe fn setIgnoresMouseEvents_(self, ignoreMouseEvents: BOOL) {
-        msg_send![self, setIgnoresMouseEvents:ignoreMouseEvents]
+        msg_send![self.as_id(), setIgnoresMouseEvents:ignoreMouseEvents]
     }
 
     unsafe fn mouseLocationOutsideOfEventStream(self) -> NSPoint {
-        msg_send![self, mouseLocationOutsideOfEventStream]
+        msg_send![self.as_id(), mouseLocationOutsideOfEventStream]
     }
 
     unsafe fn setAcceptsMouseMovedEvents_(self, acceptMouseMovedEvents: BOOL) {
-        msg_send![self, setAcceptsMouseMovedEvents:acceptMouseMovedEvents]
+        msg_send![self.as_id(), setAcceptsMouseMovedEvents:acceptMouseMovedEvents]
     }
 
     unsafe fn windowNumberAtPoint_belowWindowWithWindowNumber_(self,
                                                                point: NSPoint,
                                                                windowNumber: NSInteger) -> NSInteger {
-        msg_send![self, windowNumberAtPoint:point belowWindowWithWindowNumber:windowNumber]
+        msg_send![self.as_id(), windowNumberAtPoint:point belowWindowWithWindowNumber:windowNumber]
     }
 
     // Converting Coordinates
 
     unsafe fn backingScaleFactor(self) -> CGFloat {
-        msg_send![self, backingScaleFactor]
+        msg_send![self.as_id(), backingScaleFactor]
     }
 
     unsafe fn backingAlignedRect_options_(self, rect: NSRect, options: NSAlignmentOptions) -> NSRect {
-        msg_send![self, backingAlignedRect:rect options:options]
+        msg_send![self.as_id(), backingAlignedRect:rect options:options]
     }
 
     unsafe fn convertRectFromBacking_(self, rect: NSRect) -> NSRect {
-        msg_send![self, convertRectFromBacking:rect]
+        msg_send![self.as_id(), convertRectFromBacking:rect]
     }
 
     unsafe fn convertRectToBacking_(self, rect: NSRect) -> NSRect {
-        msg_send![self, convertRectToBacking:rect]
+        msg_send![self.as_id(), convertRectToBacking:rect]
     }
 
     unsafe fn convertRectToScreen_(self, rect: NSRect) -> NSRect {
-        msg_send![self, convertRectToScreen:rect]
+        msg_send![self.as_id(), convertRectToScreen:rect]
     }
 
     unsafe fn convertRectFromScreen_(self, rect: NSRect) -> NSRect {
-        msg_send![self, convertRectFromScreen:rect]
+        msg_send![self.as_id(), convertRectFromScreen:rect]
     }
 
     // Accessing Edited Status
 
     unsafe fn setDocumentEdited_(self, documentEdited: BOOL) {
-        msg_send![self, setDocumentEdited:documentEdited]
+        msg_send![self.as_id(), setDocumentEdited:documentEdited]
     }
 
     // Managing Titles
 
     unsafe fn title(self) -> id {
-        msg_send![self, title]
+        msg_send![self.as_id(), title]
     }
 
     unsafe fn setTitle_(self, title: id) {
-        msg_send![self, setTitle:title]
+        msg_send![self.as_id(), setTitle:title]
     }
 
     unsafe fn setTitleWithRepresentedFilename_(self, filePath: id) {
-        msg_send![self, setTitleWithRepresentedFilename:filePath]
+        msg_send![self.as_id(), setTitleWithRepresentedFilename:filePath]
     }
 
     unsafe fn setTitleVisibility_(self, visibility: NSWindowTitleVisibility) {
-        msg_send![self, setTitleVisibility:visibility]
+        msg_send![self.as_id(), setTitleVisibility:visibility]
     }
 
     unsafe fn setTitlebarAppearsTransparent_(self, transparent: BOOL) {
-        msg_send![self, setTitlebarAppearsTransparent:transparent]
+        msg_send![self.as_id(), setTitlebarAppearsTransparent:transparent]
     }
 
     unsafe fn representedFilename(self) -> id {
-        msg_send![self, representedFilename]
+        msg_send![self.as_id(), representedFilename]
     }
 
     unsafe fn setRepresentedFilename_(self, filePath: id) {
-        msg_send![self, setRepresentedFilename:filePath]
+        msg_send![self.as_id(), setRepresentedFilename:filePath]
     }
 
     unsafe fn representedURL(self) -> id {
-        msg_send![self, representedURL]
+        msg_send![self.as_id(), representedURL]
     }
 
     unsafe fn setRepresentedURL_(self, representedURL: id) {
-        msg_send![self, setRepresentedURL:representedURL]
+        msg_send![self.as_id(), setRepresentedURL:representedURL]
     }
 
     // Accessing Screen Information
 
     unsafe fn screen(self) -> id {
-        msg_send![self, screen]
+        msg_send![self.as_id(), screen]
     }
 
     unsafe fn deepestScreen(self) -> id {
-        msg_send![self, deepestScreen]
+        msg_send![self.as_id(), deepestScreen]
     }
 
     unsafe fn displaysWhenScreenProfileChanges(self) -> BOOL {
-        msg_send![self, displaysWhenScreenProfileChanges]
+        msg_send![self.as_id(), displaysWhenScreenProfileChanges]
     }
 
     unsafe fn setDisplaysWhenScreenProfileChanges_(self, displaysWhenScreenProfileChanges: BOOL) {
-        msg_send![self, setDisplaysWhenScreenProfileChanges:displaysWhenScreenProfileChanges]
+        msg_send![self.as_id(), setDisplaysWhenScreenProfileChanges:displaysWhenScreenProfileChanges]
     }
 
     // Moving Windows
 
     unsafe fn setMovableByWindowBackground_(self, movableByWindowBackground: BOOL) {
-        msg_send![self, setMovableByWindowBackground:movableByWindowBackground]
+        msg_send![self.as_id(), setMovableByWindowBackground:movableByWindowBackground]
     }
 
     unsafe fn setMovable_(self, movable: BOOL) {
-        msg_send![self, setMovable:movable]
+        msg_send![self.as_id(), setMovable:movable]
     }
 
     unsafe fn center(self) {
-        msg_send![self, center]
+        msg_send![self.as_id(), center]
     }
 
     // Closing Windows
 
     unsafe fn performClose_(self, sender: id) {
-        msg_send![self, performClose:sender]
+        msg_send![self.as_id(), performClose:sender]
     }
 
     unsafe fn close(self) {
-        msg_send![self, close]
+        msg_send![self.as_id(), close]
     }
 
     unsafe fn setReleasedWhenClosed_(self, releasedWhenClosed: BOOL) {
-        msg_send![self, setReleasedWhenClosed:releasedWhenClosed]
+        msg_send![self.as_id(), setReleasedWhenClosed:releasedWhenClosed]
     }
 
     // Minimizing Windows
 
     unsafe fn performMiniaturize_(self, sender: id) {
-        msg_send![self, performMiniaturize:sender]
+        msg_send![self.as_id(), performMiniaturize:sender]
     }
 
     unsafe fn miniaturize_(self, sender: id) {
-        msg_send![self, miniaturize:sender]
+        msg_send![self.as_id(), miniaturize:sender]
     }
 
     unsafe fn deminiaturize_(self, sender: id) {
-        msg_send![self, deminiaturize:sender]
+        msg_send![self.as_id(), deminiaturize:sender]
     }
 
     unsafe fn miniwindowImage(self) -> id {
-        msg_send![self, miniwindowImage]
+        msg_send![self.as_id(), miniwindowImage]
     }
 
     unsafe fn setMiniwindowImage_(self, miniwindowImage: id) {
-        msg_send![self, setMiniwindowImage:miniwindowImage]
+        msg_send![self.as_id(), setMiniwindowImage:miniwindowImage]
     }
 
     unsafe fn miniwindowTitle(self) -> id {
-        msg_send![self, miniwindowTitle]
+        msg_send![self.as_id(), miniwindowTitle]
     }
 
     unsafe fn setMiniwindowTitle_(self, miniwindowTitle: id) {
-        msg_send![self, setMiniwindowTitle:miniwindowTitle]
+        msg_send![self.as_id(), setMiniwindowTitle:miniwindowTitle]
     }
 
     // TODO: Getting the Dock Tile
@@ -1250,9 +1420,13 @@ impl NSWindow for id {
     // TODO: Constraint-Based Layouts
 }
 
+object_ref! {
+    pub struct NSViewRef(NSView): NSResponderRef, NSObjectRef;
+}
+
 pub trait NSView {
     unsafe fn alloc(_: Self) -> id {
-        msg_send![class("NSView"), alloc]
+        msg_send![class!(NSView), alloc]
     }
 
     unsafe fn init(self) -> id;
@@ -1272,67 +1446,71 @@ pub trait NSView {
     unsafe fn setLayer(self, layer: id);
 }
 
-impl NSView for id {
+impl<T: IsKindOf<NSViewRef>> NSView for T {
     unsafe fn init(self) -> id {
-        msg_send![self, init]
+        msg_send![self.as_id(), init]
     }
 
     unsafe fn initWithFrame_(self, frameRect: NSRect) -> id {
-        msg_send![self, initWithFrame:frameRect]
+        msg_send![self.as_id(), initWithFrame:frameRect]
     }
 
     unsafe fn bounds(self) -> NSRect {
-        msg_send![self, bounds]
+        msg_send![self.as_id(), bounds]
     }
 
     unsafe fn frame(self) -> NSRect {
-        msg_send![self, frame]
+        msg_send![self.as_id(), frame]
     }
 
     unsafe fn display_(self) {
-        msg_send![self, display]
+        msg_send![self.as_id(), display]
     }
 
     unsafe fn setWantsBestResolutionOpenGLSurface_(self, flag: BOOL) {
-        msg_send![self, setWantsBestResolutionOpenGLSurface:flag]
+        msg_send![self.as_id(), setWantsBestResolutionOpenGLSurface:flag]
     }
 
     unsafe fn convertPoint_fromView_(self, point: NSPoint, view: id) -> NSPoint {
-        msg_send![self, convertPoint:point fromView:view]
+        msg_send![self.as_id(), convertPoint:point fromView:view]
     }
 
     unsafe fn addSubview_(self, view: id) {
-        msg_send![self, addSubview:view]
+        msg_send![self.as_id(), addSubview:view]
     }
 
     unsafe fn superview(self) -> id {
-        msg_send![self, superview]
+        msg_send![self.as_id(), superview]
     }
 
     unsafe fn removeFromSuperview(self) {
-        msg_send![self, removeFromSuperview]
+        msg_send![self.as_id(), removeFromSuperview]
     }
 
     unsafe fn wantsLayer(self) -> BOOL {
-        msg_send![self, wantsLayer]
+        msg_send![self.as_id(), wantsLayer]
     }
 
     unsafe fn setWantsLayer(self, wantsLayer: BOOL) {
-        msg_send![self, setWantsLayer:wantsLayer]
+        msg_send![self.as_id(), setWantsLayer:wantsLayer]
     }
 
     unsafe fn layer(self) -> id {
-        msg_send![self, layer]
+        msg_send![self.as_id(), layer]
     }
 
     unsafe fn setLayer(self, layer: id) {
-        msg_send![self, setLayer:layer]
+        msg_send![self.as_id(), setLayer:layer]
     }
 }
 
+object_ref! {
+    pub struct NSOpenGLViewRef(NSOpenGLView): NSViewRef, NSResponderRef, NSObjectRef;
+}
+
 pub trait NSOpenGLView {
     unsafe fn alloc(_: Self) -> id {
-        msg_send![class("NSOpenGLView"), alloc]
+        msg_send![class!(NSOpenGLView), alloc]
     }
 
     unsafe fn initWithFrame_pixelFormat_(self, frameRect: NSRect, format: id) -> id;
@@ -1341,27 +1519,52 @@ pub trait NSOpenGLView {
     unsafe fn setPixelFormat_(self, pixelformat: id);
 }
 
-impl NSOpenGLView for id {
+impl<T: IsKindOf<NSOpenGLViewRef>> NSOpenGLView for T {
     unsafe fn initWithFrame_pixelFormat_(self,  frameRect: NSRect, format: id) -> id {
-        msg_send![self, initWithFrame:frameRect pixelFormat:format]
+        msg_send![self.as_id(), initWithFrame:frameRect pixelFormat:format]
     }
 
     unsafe fn display_(self) {
-        msg_send![self, display]
+        msg_send![self.as_id(), display]
     }
 
     unsafe fn setOpenGLContext_(self, context: id) {
-        msg_send![self, setOpenGLContext:context]
+        msg_send![self.as_id(), setOpenGLContext:context]
     }
 
     unsafe fn setPixelFormat_(self, pixelformat: id) {
-        msg_send![self, setPixelFormat:pixelformat]
+        msg_send![self.as_id(), setPixelFormat:pixelformat]
+    }
+}
+
+// Pass pointers as message arguments with the encoding of what they point to, such as
+// `r^I` for the pixel format attributes, rather than objc's untyped `^v`, so that
+// `verify_message` checks the pointee as well.
+#[repr(transparent)]
+struct ConstPointerArgument<T>(*const T);
+
+#[repr(transparent)]
+struct MutPointerArgument<T>(*mut T);
+
+unsafe impl<T: ::objc::Encode> ::objc::Encode for ConstPointerArgument<T> {
+    fn encode() -> ::objc::Encoding {
+        unsafe { ::objc::Encoding::from_str(&format!("r^{}", T::encode().as_str())) }
     }
 }
 
+unsafe impl<T: ::objc::Encode> ::objc::Encode for MutPointerArgument<T> {
+    fn encode() -> ::objc::Encoding {
+        unsafe { ::objc::Encoding::from_str(&format!("^{}", T::encode().as_str())) }
+    }
+}
+
+object_ref! {
+    pub struct NSOpenGLPixelFormatRef(NSOpenGLPixelFormat): NSObjectRef;
+}
+
 pub trait NSOpenGLPixelFormat {
     unsafe fn alloc(_: Self) -> id {
-        msg_send![class("NSOpenGLPixelFormat"), alloc]
+        msg_send![class!(NSOpenGLPixelFormat), alloc]
     }
 
     // Creating an NSOpenGLPixelFormat Object
@@ -1375,27 +1578,31 @@ pub trait NSOpenGLPixelFormat {
 
 }
 
-impl NSOpenGLPixelFormat for id {
+impl<T: IsKindOf<NSOpenGLPixelFormatRef>> NSOpenGLPixelFormat for T {
     // Creating an NSOpenGLPixelFormat Object
 
     unsafe fn initWithAttributes_(self, attributes: &[u32]) -> id {
-        msg_send![self, initWithAttributes:attributes]
+        msg_send![self.as_id(), initWithAttributes:ConstPointerArgument(attributes.as_ptr())]
     }
 
     // Managing the Pixel Format
 
     unsafe fn getValues_forAttribute_forVirtualScreen_(self, val: *mut GLint, attrib: NSOpenGLPixelFormatAttribute, screen: GLint) {
-        msg_send![self, getValues:val forAttribute:attrib forVirtualScreen:screen]
+        msg_send![self.as_id(), getValues:MutPointerArgument(val) forAttribute:attrib forVirtualScreen:screen]
     }
 
     unsafe fn numberOfVirtualScreens(self) -> GLint {
-        msg_send![self, numberOfVirtualScreens]
+        msg_send![self.as_id(), numberOfVirtualScreens]
     }
 }
 
+object_ref! {
+    pub struct NSOpenGLContextRef(NSOpenGLContext): NSObjectRef;
+}
+
 pub trait NSOpenGLContext {
     unsafe fn alloc(_: Self) -> id {
-        msg_send![class("NSOpenGLContext"), alloc]
+        msg_send![class!(NSOpenGLContext), alloc]
     }
 
     // Context Creation
@@ -1428,79 +1635,79 @@ pub trait NSOpenGLContext {
     unsafe fn CGLContextObj(self) -> CGLContextObj;
 }
 
-impl NSOpenGLContext for id {
+impl<T: IsKindOf<NSOpenGLContextRef>> NSOpenGLContext for T {
     // Context Creation
 
     unsafe fn initWithFormat_shareContext_(self, format: id /* (NSOpenGLPixelFormat *) */, shareContext: id /* (NSOpenGLContext *) */) -> id /* (instancetype) */ {
-        msg_send![self, initWithFormat:format shareContext:shareContext]
+        msg_send![self.as_id(), initWithFormat:format shareContext:shareContext]
     }
 
     unsafe fn initWithCGLContextObj_(self, context: CGLContextObj) -> id /* (instancetype) */ {
-        msg_send![self, initWithCGLContextObj:context]
+        msg_send![self.as_id(), initWithCGLContextObj:context]
     }
 
     // Managing the Current Context
 
     unsafe fn clearCurrentContext(_: Self) {
-        msg_send![class("NSOpenGLContext"), clearCurrentContext]
+        msg_send![class!(NSOpenGLContext), clearCurrentContext]
     }
 
     unsafe fn currentContext(_: Self) -> id /* (NSOpenGLContext *) */ {
-        msg_send![class("NSOpenGLContext"), currentContext]
+        msg_send![class!(NSOpenGLContext), currentContext]
     }
 
     unsafe fn makeCurrentContext(self) {
-        msg_send![self, makeCurrentContext]
+        msg_send![self.as_id(), makeCurrentContext]
     }
 
     // Drawable Object Management
 
     unsafe fn setView_(self, view: id /* (NSView *) */) {
-        msg_send![self, setView:view]
+        msg_send![self.as_id(), setView:view]
     }
 
     unsafe fn view(self) -> id /* (NSView *) */ {
-        msg_send![self, view]
+        msg_send![self.as_id(), view]
     }
 
     unsafe fn clearDrawable(self) {
-        msg_send![self, clearDrawable]
+        msg_send![self.as_id(), clearDrawable]
     }
 
     unsafe fn update(self) {
-        msg_send![self, update]
+        msg_send![self.as_id(), update]
     }
 
     // Flushing the Drawing Buffer
 
     unsafe fn flushBuffer(self) {
-        msg_send![self, flushBuffer]
+        msg_send![self.as_id(), flushBuffer]
     }
 
     // Context Parameter Handling
 
     unsafe fn setValues_forParameter_(self, vals: *const GLint, param: NSOpenGLContextParameter) {
-        msg_send![self, setValues:vals forParameter:param]
+        msg_send![self.as_id(), setValues:ConstPointerArgument(vals) forParameter:param]
     }
 
     unsafe fn getValues_forParameter_(self, vals: *mut GLint, param: NSOpenGLContextParameter) {
-        msg_send![self, getValues:vals forParameter:param]
+        msg_send![self.as_id(), getValues:MutPointerArgument(vals) forParameter:param]
     }
 
     // Working with Virtual Screens
 
     unsafe fn setCurrentVirtualScreen_(self, screen: GLint) {
-        msg_send![self, setCurrentVirtualScreen:screen]
+        msg_send![self.as_id(), setCurrentVirtualScreen:screen]
     }
 
     unsafe fn currentVirtualScreen(self) -> GLint {
-        msg_send![self, currentVirtualScreen]
+        msg_send![self.as_id(), currentVirtualScreen]
     }
 
     // Getting the CGL Context Object
 
     unsafe fn CGLContextObj(self) -> CGLContextObj {
-        msg_send![self, CGLContextObj]
+        msg_send![self.as_id(), CGLContextObj]
     }
 }
 
@@ -1511,7 +1718,8 @@ bitflags! {
     }
 }
 
-#[repr(i64)] // NSInteger
+#[cfg_attr(target_pointer_width = "32", repr(i32))]
+#[cfg_attr(target_pointer_width = "64", repr(i64))]
 pub enum NSEventGestureAxis {
     NSEventGestureAxisNone = 0,
     NSEventGestureAxisHorizontal,
@@ -1545,7 +1753,8 @@ bitflags! {
 }
 
 #[derive(Debug)]
-#[repr(u64)] // NSUInteger
+#[cfg_attr(target_pointer_width = "32", repr(u32))]
+#[cfg_attr(target_pointer_width = "64", repr(u64))]
 pub enum NSEventType {
     NSLeftMouseDown         = 1,
     NSLeftMouseUp           = 2,
@@ -1666,6 +1875,19 @@ pub enum NSEventSubtype {
     NSAWTEventType = 16,
 }
 
+encode_as! {
+    NSEventSwipeTrackingOptions => NSUInteger,
+    NSEventGestureAxis => NSInteger,
+    NSEventPhase => NSUInteger,
+    NSTouchPhase => NSUInteger,
+    NSEventType => NSUInteger,
+    NSEventMask => libc::c_ulonglong,
+    NSEventModifierFlags => NSUInteger,
+    NSPointingDeviceType => NSUInteger,
+    NSEventButtonMask => NSUInteger,
+    NSEventSubtype => i16,
+}
+
 pub const NSUpArrowFunctionKey: libc::c_ushort = 0xF700;
 pub const NSDownArrowFunctionKey: libc::c_ushort = 0xF701;
 pub const NSLeftArrowFunctionKey: libc::c_ushort = 0xF702;
@@ -1739,6 +1961,10 @@ pub const NSFindFunctionKey: libc::c_ushort = 0xF745;
 pub const NSHelpFunctionKey: libc::c_ushort = 0xF746;
 pub const NSModeSwitchFunctionKey: libc::c_ushort = 0xF747;
 
+object_ref! {
+    pub struct NSEventRef(NSEvent): NSObjectRef;
+}
+
 pub trait NSEvent {
     // Creating Events
     unsafe fn keyEventWithType_location_modifierFlags_timestamp_windowNumber_context_characters_charactersIgnoringModifiers_isARepeat_keyCode_(
@@ -1882,10 +2108,11 @@ pub trait NSEvent {
     // TODO: trackSwipeEventWithOptions_dampenAmountThresholdMin_max_usingHandler_ (unsure how to bind to blocks)
 
     // Converting a Mouse Event’s Position into a Sprite Kit Node’s Coordinate Space
+    #[cfg(target_os = "macos")]
     unsafe fn locationInNode_(self, node: id /* (SKNode *) */) -> CGPoint;
 }
 
-impl NSEvent for id {
+impl<T: IsKindOf<NSEventRef>> NSEvent for T {
     // Creating Events
 
     unsafe fn keyEventWithType_location_modifierFlags_timestamp_windowNumber_context_characters_charactersIgnoringModifiers_isARepeat_keyCode_(
@@ -1901,16 +2128,16 @@ impl NSEvent for id {
         repeatKey: BOOL,
         code: libc::c_ushort) -> id /* (NSEvent *) */
     {
-        msg_send![class("NSEvent"), keyEventWithType:eventType
-                                            location:location
-                                       modifierFlags:modifierFlags
-                                           timestamp:timestamp
-                                        windowNumber:windowNumber
-                                             context:context
-                                          characters:characters
-                         charactersIgnoringModifiers:unmodCharacters
-                                           isARepeat:repeatKey
-                                             keyCode:code]
+        msg_send![class!(NSEvent), keyEventWithType:eventType
+                                           location:location
+                                      modifierFlags:modifierFlags
+                                          timestamp:timestamp
+                                       windowNumber:windowNumber
+                                            context:context
+                                         characters:characters
+                        charactersIgnoringModifiers:unmodCharacters
+                                          isARepeat:repeatKey
+                                            keyCode:code]
     }
 
     unsafe fn mouseEventWithType_location_modifierFlags_timestamp_windowNumber_context_eventNumber_clickCount_pressure_(
@@ -1925,15 +2152,15 @@ impl NSEvent for id {
         clickCount: NSInteger,
         pressure: libc::c_float) -> id /* (NSEvent *) */
     {
-        msg_send![class("NSEvent"), mouseEventWithType:eventType
-                                              location:location
-                                         modifierFlags:modifierFlags
-                                             timestamp:timestamp
-                                          windowNumber:windowNumber
-                                               context:context
-                                           eventNumber:eventNumber
-                                            clickCount:clickCount
-                                              pressure:pressure]
+        msg_send![class!(NSEvent), mouseEventWithType:eventType
+                                             location:location
+                                        modifierFlags:modifierFlags
+                                            timestamp:timestamp
+                                         windowNumber:windowNumber
+                                              context:context
+                                          eventNumber:eventNumber
+                                           clickCount:clickCount
+                                             pressure:pressure]
     }
 
     unsafe fn enterExitEventWithType_location_modifierFlags_timestamp_windowNumber_context_eventNumber_trackingNumber_userData_(
@@ -1948,15 +2175,15 @@ impl NSEvent for id {
         trackingNumber: NSInteger,
         userData: *mut libc::c_void) -> id /* (NSEvent *) */
     {
-        msg_send![class("NSEvent"), enterExitEventWithType:eventType
-                                                  location:location
-                                             modifierFlags:modifierFlags
-                                                 timestamp:timestamp
-                                              windowNumber:windowNumber
-                                                   context:context
-                                               eventNumber:eventNumber
-                                            trackingNumber:trackingNumber
-                                                  userData:userData]
+        msg_send![class!(NSEvent), enterExitEventWithType:eventType
+                                                 location:location
+                                            modifierFlags:modifierFlags
+                                                timestamp:timestamp
+                                             windowNumber:windowNumber
+                                                  context:context
+                                              eventNumber:eventNumber
+                                           trackingNumber:trackingNumber
+                                                 userData:userData]
     }
 
     unsafe fn otherEventWithType_location_modifierFlags_timestamp_windowNumber_context_subtype_data1_data2_(
@@ -1971,62 +2198,62 @@ impl NSEvent for id {
         data1: NSInteger,
         data2: NSInteger) -> id /* (NSEvent *) */
     {
-        msg_send![class("NSEvent"), otherEventWithType:eventType
-                                              location:location
-                                         modifierFlags:modifierFlags
-                                             timestamp:timestamp
-                                          windowNumber:windowNumber
-                                               context:context
-                                               subtype:subtype
-                                                 data1:data1
-                                                 data2:data2]
+        msg_send![class!(NSEvent), otherEventWithType:eventType
+                                             location:location
+                                        modifierFlags:modifierFlags
+                                            timestamp:timestamp
+                                         windowNumber:windowNumber
+                                              context:context
+                                              subtype:subtype
+                                                data1:data1
+                                                data2:data2]
     }
 
     unsafe fn eventWithEventRef_(_: Self, eventRef: *const libc::c_void) -> id {
-        msg_send![class("NSEvent"), eventWithEventRef:eventRef]
+        msg_send![class!(NSEvent), eventWithEventRef:eventRef]
     }
 
     unsafe fn eventWithCGEvent_(_: Self, cgEvent: *mut libc::c_void /* CGEventRef */) -> id {
-        msg_send![class("NSEvent"), eventWithCGEvent:cgEvent]
+        msg_send![class!(NSEvent), eventWithCGEvent:cgEvent]
     }
 
     // Getting General Event Information
 
     unsafe fn context(self) -> id /* (NSGraphicsContext *) */ {
-        msg_send![self, context]
+        msg_send![self.as_id(), context]
     }
 
     unsafe fn locationInWindow(self) -> NSPoint {
-        msg_send![self, locationInWindow]
+        msg_send![self.as_id(), locationInWindow]
     }
 
     unsafe fn modifierFlags(self) -> NSEventModifierFlags {
-        msg_send![self, modifierFlags]
+        msg_send![self.as_id(), modifierFlags]
     }
 
     unsafe fn timestamp(self) -> NSTimeInterval {
-        msg_send![self, timestamp]
+        msg_send![self.as_id(), timestamp]
     }
     // NOTE: renamed from `- type` due to Rust keyword collision
 
     unsafe fn eventType(self) -> NSEventType {
-        msg_send![self, type]
+        msg_send![self.as_id(), type]
     }
 
     unsafe fn window(self) -> id /* (NSWindow *) */ {
-        msg_send![self, window]
+        msg_send![self.as_id(), window]
     }
 
     unsafe fn windowNumber(self) -> NSInteger {
-        msg_send![self, windowNumber]
+        msg_send![self.as_id(), windowNumber]
     }
 
     unsafe fn eventRef(self) -> *const libc::c_void {
-        msg_send![self, eventRef]
+        msg_send![self.as_id(), eventRef]
     }
 
     unsafe fn CGEvent(self) -> *mut libc::c_void /* CGEventRef */ {
-        msg_send![self, CGEvent]
+        msg_send![self.as_id(), CGEvent]
     }
 
     // Getting Key Event Information
@@ -2034,211 +2261,211 @@ impl NSEvent for id {
     // NOTE: renamed from `+ modifierFlags` due to conflict with `- modifierFlags`
 
     unsafe fn currentModifierFlags(_: Self) -> NSEventModifierFlags {
-        msg_send![class("NSEvent"), currentModifierFlags]
+        msg_send![class!(NSEvent), currentModifierFlags]
     }
 
     unsafe fn keyRepeatDelay(_: Self) -> NSTimeInterval {
-        msg_send![class("NSEvent"), keyRepeatDelay]
+        msg_send![class!(NSEvent), keyRepeatDelay]
     }
 
     unsafe fn keyRepeatInterval(_: Self) -> NSTimeInterval {
-        msg_send![class("NSEvent"), keyRepeatInterval]
+        msg_send![class!(NSEvent), keyRepeatInterval]
     }
 
     unsafe fn characters(self) -> id /* (NSString *) */ {
-        msg_send![self, characters]
+        msg_send![self.as_id(), characters]
     }
 
     unsafe fn charactersIgnoringModifiers(self) -> id /* (NSString *) */ {
-        msg_send![self, charactersIgnoringModifiers]
+        msg_send![self.as_id(), charactersIgnoringModifiers]
     }
 
     unsafe fn keyCode(self) -> libc::c_ushort {
-        msg_send![self, keyCode]
+        msg_send![self.as_id(), keyCode]
     }
 
     // Getting Mouse Event Information
 
     unsafe fn pressedMouseButtons(_: Self) -> NSUInteger {
-        msg_send![class("NSEvent"), pressedMouseButtons]
+        msg_send![class!(NSEvent), pressedMouseButtons]
     }
 
     unsafe fn doubleClickInterval(_: Self) -> NSTimeInterval {
-        msg_send![class("NSEvent"), doubleClickInterval]
+        msg_send![class!(NSEvent), doubleClickInterval]
     }
 
     unsafe fn mouseLocation(_: Self) -> NSPoint {
-        msg_send![class("NSEvent"), mouseLocation]
+        msg_send![class!(NSEvent), mouseLocation]
     }
 
     unsafe fn buttonNumber(self) -> NSInteger {
-        msg_send![self, buttonNumber]
+        msg_send![self.as_id(), buttonNumber]
     }
 
     unsafe fn clickCount(self) -> NSInteger {
-        msg_send![self, clickCount]
+        msg_send![self.as_id(), clickCount]
     }
 
     unsafe fn pressure(self) -> libc::c_float {
-        msg_send![self, pressure]
+        msg_send![self.as_id(), pressure]
     }
 
     unsafe fn stage(self) -> NSInteger{
-        msg_send![self, stage]
+        msg_send![self.as_id(), stage]
     }
 
     unsafe fn setMouseCoalescingEnabled_(_: Self, flag: BOOL) {
-        msg_send![class("NSEvent"), setMouseCoalescingEnabled:flag]
+        msg_send![class!(NSEvent), setMouseCoalescingEnabled:flag]
     }
 
     unsafe fn isMouseCoalescingEnabled(_: Self) -> BOOL {
-        msg_send![class("NSEvent"), isMouseCoalescingEnabled]
+        msg_send![class!(NSEvent), isMouseCoalescingEnabled]
     }
 
     // Getting Mouse-Tracking Event Information
 
     unsafe fn eventNumber(self) -> NSInteger {
-        msg_send![self, eventNumber]
+        msg_send![self.as_id(), eventNumber]
     }
 
     unsafe fn trackingNumber(self) -> NSInteger {
-        msg_send![self, trackingNumber]
+        msg_send![self.as_id(), trackingNumber]
     }
 
     unsafe fn trackingArea(self) -> id /* (NSTrackingArea *) */ {
-        msg_send![self, trackingArea]
+        msg_send![self.as_id(), trackingArea]
     }
 
     unsafe fn userData(self) -> *const libc::c_void {
-        msg_send![self, userData]
+        msg_send![self.as_id(), userData]
     }
 
     // Getting Custom Event Information
 
     unsafe fn data1(self) -> NSInteger {
-        msg_send![self, data1]
+        msg_send![self.as_id(), data1]
     }
 
     unsafe fn data2(self) -> NSInteger {
-        msg_send![self, data2]
+        msg_send![self.as_id(), data2]
     }
 
     unsafe fn subtype(self) -> NSEventSubtype {
-        msg_send![self, subtype]
+        msg_send![self.as_id(), subtype]
     }
 
     // Getting Scroll Wheel Event Information
 
     unsafe fn deltaX(self) -> CGFloat {
-        msg_send![self, deltaX]
+        msg_send![self.as_id(), deltaX]
     }
 
     unsafe fn deltaY(self) -> CGFloat {
-        msg_send![self, deltaY]
+        msg_send![self.as_id(), deltaY]
     }
 
     unsafe fn deltaZ(self) -> CGFloat {
-        msg_send![self, deltaZ]
+        msg_send![self.as_id(), deltaZ]
     }
 
     // Getting Tablet Proximity Information
 
     unsafe fn capabilityMask(self) -> NSUInteger {
-        msg_send![self, capabilityMask]
+        msg_send![self.as_id(), capabilityMask]
     }
 
     unsafe fn deviceID(self) -> NSUInteger {
-        msg_send![self, deviceID]
+        msg_send![self.as_id(), deviceID]
     }
 
     unsafe fn pointingDeviceID(self) -> NSUInteger {
-        msg_send![self, pointingDeviceID]
+        msg_send![self.as_id(), pointingDeviceID]
     }
 
     unsafe fn pointingDeviceSerialNumber(self) -> NSUInteger {
-        msg_send![self, pointingDeviceSerialNumber]
+        msg_send![self.as_id(), pointingDeviceSerialNumber]
     }
 
     unsafe fn pointingDeviceType(self) -> NSPointingDeviceType {
-        msg_send![self, pointingDeviceType]
+        msg_send![self.as_id(), pointingDeviceType]
     }
 
     unsafe fn systemTabletID(self) -> NSUInteger {
-        msg_send![self, systemTabletID]
+        msg_send![self.as_id(), systemTabletID]
     }
 
     unsafe fn tabletID(self) -> NSUInteger {
-        msg_send![self, tabletID]
+        msg_send![self.as_id(), tabletID]
     }
 
     unsafe fn uniqueID(self) -> libc::c_ulonglong {
-        msg_send![self, uniqueID]
+        msg_send![self.as_id(), uniqueID]
     }
 
     unsafe fn vendorID(self) -> NSUInteger {
-        msg_send![self, vendorID]
+        msg_send![self.as_id(), vendorID]
     }
 
     unsafe fn vendorPointingDeviceType(self) -> NSUInteger {
-        msg_send![self, vendorPointingDeviceType]
+        msg_send![self.as_id(), vendorPointingDeviceType]
     }
 
     // Getting Tablet Pointing Information
 
     unsafe fn absoluteX(self) -> NSInteger {
-        msg_send![self, absoluteX]
+        msg_send![self.as_id(), absoluteX]
     }
 
     unsafe fn absoluteY(self) -> NSInteger {
-        msg_send![self, absoluteY]
+        msg_send![self.as_id(), absoluteY]
     }
 
     unsafe fn absoluteZ(self) -> NSInteger {
-        msg_send![self, absoluteZ]
+        msg_send![self.as_id(), absoluteZ]
     }
 
     unsafe fn buttonMask(self) -> NSEventButtonMask {
-        msg_send![self, buttonMask]
+        msg_send![self.as_id(), buttonMask]
     }
 
     unsafe fn rotation(self) -> libc::c_float {
-        msg_send![self, rotation]
+        msg_send![self.as_id(), rotation]
     }
 
     unsafe fn tangentialPressure(self) -> libc::c_float {
-        msg_send![self, tangentialPressure]
+        msg_send![self.as_id(), tangentialPressure]
     }
 
     unsafe fn tilt(self) -> NSPoint {
-        msg_send![self, tilt]
+        msg_send![self.as_id(), tilt]
     }
 
     unsafe fn vendorDefined(self) -> id {
-        msg_send![self, vendorDefined]
+        msg_send![self.as_id(), vendorDefined]
     }
 
     // Requesting and Stopping Periodic Events
 
     unsafe fn startPeriodicEventsAfterDelay_withPeriod_(_: Self, delaySeconds: NSTimeInterval, periodSeconds: NSTimeInterval) {
-        msg_send![class("NSEvent"), startPeriodicEventsAfterDelay:delaySeconds withPeriod:periodSeconds]
+        msg_send![class!(NSEvent), startPeriodicEventsAfterDelay:delaySeconds withPeriod:periodSeconds]
     }
 
     unsafe fn stopPeriodicEvents(_: Self) {
-        msg_send![class("NSEvent"), stopPeriodicEvents]
+        msg_send![class!(NSEvent), stopPeriodicEvents]
     }
 
     // Getting Touch and Gesture Information
 
     unsafe fn magnification(self) -> CGFloat {
-        msg_send![self, magnification]
+        msg_send![self.as_id(), magnification]
     }
 
     unsafe fn touchesMatchingPhase_inView_(self, phase: NSTouchPhase, view: id /* (NSView *) */) -> id /* (NSSet *) */ {
-        msg_send![self, touchesMatchingPhase:phase inView:view]
+        msg_send![self.as_id(), touchesMatchingPhase:phase inView:view]
     }
 
     unsafe fn isSwipeTrackingFromScrollEventsEnabled(_: Self) -> BOOL {
-        msg_send![class("NSEvent"), isSwipeTrackingFromScrollEventsEnabled]
+        msg_send![class!(NSEvent), isSwipeTrackingFromScrollEventsEnabled]
     }
 
     // Monitoring Application Events
@@ -2247,39 +2474,45 @@ impl NSEvent for id {
     // TODO: addLocalMonitorForEventsMatchingMask_handler_ (unsure how to bind to blocks)
 
     unsafe fn removeMonitor_(_: Self, eventMonitor: id) {
-        msg_send![class("NSEvent"), removeMonitor:eventMonitor]
+        msg_send![class!(NSEvent), removeMonitor:eventMonitor]
     }
 
     // Scroll Wheel and Flick Events
 
     unsafe fn hasPreciseScrollingDeltas(self) -> BOOL {
-        msg_send![self, hasPreciseScrollingDeltas]
+        msg_send![self.as_id(), hasPreciseScrollingDeltas]
     }
 
     unsafe fn scrollingDeltaX(self) -> CGFloat {
-        msg_send![self, scrollingDeltaX]
+        msg_send![self.as_id(), scrollingDeltaX]
     }
 
     unsafe fn scrollingDeltaY(self) -> CGFloat {
-        msg_send![self, scrollingDeltaY]
+        msg_send![self.as_id(), scrollingDeltaY]
     }
 
     unsafe fn momentumPhase(self) -> NSEventPhase {
-        msg_send![self, momentumPhase]
+        msg_send![self.as_id(), momentumPhase]
     }
 
     unsafe fn phase(self) -> NSEventPhase {
-        msg_send![self, phase]
+        msg_send![self.as_id(), phase]
     }
 
     // TODO: trackSwipeEventWithOptions_dampenAmountThresholdMin_max_usingHandler_ (unsure how to bind to blocks)
 
     // Converting a Mouse Event’s Position into a Sprite Kit Node’s Coordinate Space
+    #[cfg(target_os = "macos")]
     unsafe fn locationInNode_(self, node: id /* (SKNode *) */) -> CGPoint {
-        msg_send![self, locationInNode:node]
+        let location: NSPoint = msg_send![self.as_id(), locationInNode:node];
+        location.into()
     }
 }
 
+object_ref! {
+    pub struct NSScreenRef(NSScreen): NSObjectRef;
+}
+
 pub trait NSScreen {
     // Getting NSScreen Objects
     unsafe fn mainScreen(_: Self) -> id /* (NSScreen *) */;
@@ -2302,83 +2535,96 @@ pub trait NSScreen {
     unsafe fn convertRectToBacking_(self, aRect: NSRect) -> NSRect;
 }
 
-impl NSScreen for id {
+impl<T: IsKindOf<NSScreenRef>> NSScreen for T {
     // Getting NSScreen Objects
 
     unsafe fn mainScreen(_: Self) -> id /* (NSScreen *) */ {
-        msg_send![class("NSScreen"), mainScreen]
+        msg_send![class!(NSScreen), mainScreen]
     }
 
     unsafe fn deepestScreen(_: Self) -> id /* (NSScreen *) */ {
-        msg_send![class("NSScreen"), deepestScreen]
+        msg_send![class!(NSScreen), deepestScreen]
     }
 
     unsafe fn screens(_: Self) -> id /* (NSArray *) */ {
-        msg_send![class("NSScreen"), screens]
+        msg_send![class!(NSScreen), screens]
     }
 
     // Getting Screen Information
 
     unsafe fn depth(self) -> NSWindowDepth {
-        msg_send![self, depth]
+        msg_send![self.as_id(), depth]
     }
 
     unsafe fn frame(self) -> NSRect {
-        msg_send![self, frame]
+        msg_send![self.as_id(), frame]
     }
 
     unsafe fn supportedWindowDepths(self) -> *const NSWindowDepth {
-        msg_send![self, supportedWindowDepths]
+        let depths: *const libc::c_void = msg_send![self.as_id(), supportedWindowDepths];
+        depths as *const NSWindowDepth
     }
 
     unsafe fn deviceDescription(self) -> id /* (NSDictionary *) */ {
-        msg_send![self, deviceDescription]
+        msg_send![self.as_id(), deviceDescription]
     }
 
     unsafe fn visibleFrame(self) -> NSRect {
-        msg_send![self, visibleFrame]
+        msg_send![self.as_id(), visibleFrame]
     }
 
     unsafe fn colorSpace(self) -> id /* (NSColorSpace *) */ {
-        msg_send![self, colorSpace]
+        msg_send![self.as_id(), colorSpace]
     }
 
     unsafe fn screensHaveSeparateSpaces(_: Self) -> BOOL {
-        msg_send![class("NSScreen"), screensHaveSeparateSpaces]
+        msg_send![class!(NSScreen), screensHaveSeparateSpaces]
     }
 
     // Screen Backing Coordinate Conversion
 
     unsafe fn backingAlignedRect_options_(self, aRect: NSRect, options: NSAlignmentOptions) -> NSRect {
-        msg_send![self, backingAlignedRect:aRect options:options]
+        msg_send![self.as_id(), backingAlignedRect:aRect options:options]
     }
 
     unsafe fn backingScaleFactor(self) -> CGFloat {
-        msg_send![self, backingScaleFactor]
+        msg_send![self.as_id(), backingScaleFactor]
     }
 
     unsafe fn convertRectFromBacking_(self, aRect: NSRect) -> NSRect {
-        msg_send![self, convertRectFromBacking:aRect]
+        msg_send![self.as_id(), convertRectFromBacking:aRect]
     }
 
     unsafe fn convertRectToBacking_(self, aRect: NSRect) -> NSRect {
-        msg_send![self, convertRectToBacking:aRect]
+        msg_send![self.as_id(), convertRectToBacking:aRect]
     }
 }
 
+object_ref! {
+    pub struct NSControlRef(NSControl): NSViewRef, NSResponderRef, NSObjectRef;
+}
+
+object_ref! {
+    pub struct NSButtonRef(NSButton): NSControlRef, NSViewRef, NSResponderRef, NSObjectRef;
+}
+
 pub trait NSButton {
      unsafe fn setImage_(self, img: id /* (NSImage *) */);
 }
 
-impl NSButton for id {
+impl<T: IsKindOf<NSButtonRef>> NSButton for T {
     unsafe fn setImage_(self, img: id /* (NSImage *) */) {
-        msg_send![self, setImage:img]
+        msg_send![self.as_id(), setImage:img]
     }
 }
 
+object_ref! {
+    pub struct NSImageRef(NSImage): NSObjectRef;
+}
+
 pub trait NSImage {
     unsafe fn alloc(_: Self) -> id {
-        msg_send![class("NSImage"), alloc]
+        msg_send![class!(NSImage), alloc]
     }
 
     unsafe fn initByReferencingFile_(self, file_name: id /* (NSString *) */) -> id;
@@ -2387,27 +2633,31 @@ pub trait NSImage {
     unsafe fn setName_(self, name: id /* (NSString *) */) -> BOOL;
 }
 
-impl NSImage for id {
+impl<T: IsKindOf<NSImageRef>> NSImage for T {
     unsafe fn initByReferencingFile_(self, file_name: id /* (NSString *) */) -> id {
-        msg_send![self, initByReferencingFile:file_name]
+        msg_send![self.as_id(), initByReferencingFile:file_name]
     }
 
     unsafe fn initWithContentsOfFile_(self, file_name: id /* (NSString *) */) -> id {
-        msg_send![self, initWithContentsOfFile:file_name]
+        msg_send![self.as_id(), initWithContentsOfFile:file_name]
     }
 
     unsafe fn name(self) -> id /* (NSString *) */ {
-        msg_send![self, name]
+        msg_send![self.as_id(), name]
     }
 
     unsafe fn setName_(self, name: id /* (NSString *) */) -> BOOL {
-        msg_send![self, setName:name]
+        msg_send![self.as_id(), setName:name]
     }
 }
 
 pub const NSVariableStatusItemLength: CGFloat = -1.0;
 pub const NSSquareStatusItemLength: CGFloat = -2.0;
 
+object_ref! {
+    pub struct NSStatusItemRef(NSStatusItem): NSObjectRef;
+}
+
 pub trait NSStatusItem {
     unsafe fn statusBar(self) -> id /* (NSStatusBar *) */;
     unsafe fn button(self) -> id /* (NSStatusBarButton *) */;
@@ -2417,35 +2667,39 @@ pub trait NSStatusItem {
     unsafe fn setLength_(self, length: CGFloat);
 }
 
-impl NSStatusItem for id {
+impl<T: IsKindOf<NSStatusItemRef>> NSStatusItem for T {
     unsafe fn statusBar(self) -> id /* (NSStatusBar *) */ {
-        msg_send![self, statusBar]
+        msg_send![self.as_id(), statusBar]
     }
 
     unsafe fn button(self) -> id /* (NSStatusBarButton *) */ {
-        msg_send![self, button]
+        msg_send![self.as_id(), button]
     }
 
     unsafe fn menu(self) -> id {
-        msg_send![self, menu]
+        msg_send![self.as_id(), menu]
     }
 
     unsafe fn setMenu_(self, menu: id) {
-        msg_send![self, setMenu:menu]
+        msg_send![self.as_id(), setMenu:menu]
     }
 
     unsafe fn length(self) -> CGFloat {
-        msg_send![self, length]
+        msg_send![self.as_id(), length]
     }
 
     unsafe fn setLength_(self, length: CGFloat) {
-        msg_send![self, setLength: length]
+        msg_send![self.as_id(), setLength: length]
     }
 }
 
+object_ref! {
+    pub struct NSStatusBarRef(NSStatusBar): NSObjectRef;
+}
+
 pub trait NSStatusBar {
     unsafe fn systemStatusBar(_: Self) -> id {
-        msg_send![class("NSStatusBar"), systemStatusBar]
+        msg_send![class!(NSStatusBar), systemStatusBar]
     }
 
     unsafe fn statusItemWithLength_(self, length: CGFloat) -> id /* (NSStatusItem *) */;
@@ -2453,16 +2707,16 @@ pub trait NSStatusBar {
     unsafe fn isVertical(self) -> BOOL;
 }
 
-impl NSStatusBar for id {
+impl<T: IsKindOf<NSStatusBarRef>> NSStatusBar for T {
     unsafe fn statusItemWithLength_(self, length: CGFloat) -> id /* (NSStatusItem *) */ {
-        msg_send![self, statusItemWithLength:length]
+        msg_send![self.as_id(), statusItemWithLength:length]
     }
 
     unsafe fn removeStatusItem_(self, item: id /* (NSStatusItem *) */) {
-        msg_send![self, removeStatusItem:item]
+        msg_send![self.as_id(), removeStatusItem:item]
     }
 
     unsafe fn isVertical(self) -> BOOL {
-        msg_send![self, isVertical]
+        msg_send![self.as_id(), isVertical]
     }
 }
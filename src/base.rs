@@ -7,8 +7,13 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use libc::{c_char, c_void};
 use objc::runtime;
+use std::error::Error;
+use std::fmt;
 use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
 
 pub use objc::runtime::{BOOL, NO, YES};
 
@@ -23,27 +28,170 @@ pub const nil: id = 0 as id;
 pub const Nil: Class = 0 as Class;
 
 /// A convenience method to convert the name of a class to the class object itself.
+///
+/// This returns `Nil` if the class isn't loaded; `try_class` says so instead, and the
+/// `class!` macro only looks the class up once.
 #[inline]
 pub fn class(name: &str) -> Class {
-    unsafe {
-        mem::transmute(runtime::Class::get(name))
+    try_class(name).unwrap_or(Nil)
+}
+
+/// Looks up the class named `name`, failing if no such class is loaded.
+pub fn try_class(name: &str) -> Result<Class, ClassNotFound> {
+    match runtime::Class::get(name) {
+        Some(cls) => Ok(cls as *const runtime::Class as Class),
+        None => Err(ClassNotFound { name: name.to_owned() }),
+    }
+}
+
+/// The error returned when a class isn't loaded in the process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClassNotFound {
+    name: String,
+}
+
+impl ClassNotFound {
+    /// The name of the missing class.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for ClassNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "class `{}` is not loaded; is the framework that defines it linked?",
+               self.name)
+    }
+}
+
+impl Error for ClassNotFound {}
+
+/// A class which is looked up the first time it is used, and then kept for the rest of
+/// the process. `class!` declares one per call site.
+pub struct CachedClass {
+    class: AtomicPtr<runtime::Class>,
+}
+
+impl CachedClass {
+    pub const fn new() -> CachedClass {
+        CachedClass { class: AtomicPtr::new(ptr::null_mut()) }
+    }
+
+    /// Returns the class named `name`, looking it up if this is the first call.
+    ///
+    /// Classes are never unloaded, so a lookup that races with another thread finds the
+    /// same class, and either result can be kept.
+    #[inline]
+    pub fn try_get(&self, name: &str) -> Result<Class, ClassNotFound> {
+        let cls = self.class.load(Ordering::Acquire);
+        if !cls.is_null() {
+            return Ok(cls);
+        }
+        let cls = try_class(name)?;
+        self.class.store(cls, Ordering::Release);
+        Ok(cls)
+    }
+
+    /// Like `try_get`, but panics if the class isn't loaded.
+    #[inline]
+    pub fn get(&self, name: &str) -> Class {
+        match self.try_get(name) {
+            Ok(cls) => cls,
+            Err(err) => panic!("{}", err),
+        }
+    }
+}
+
+impl Default for CachedClass {
+    fn default() -> CachedClass {
+        CachedClass::new()
     }
 }
 
 /// A convenience method to convert the name of a selector to the selector object.
+///
+/// This registers the name on every call; the `selector!` macro only does once.
 #[inline]
 pub fn selector(name: &str) -> SEL {
     runtime::Sel::register(name)
 }
 
-#[cfg(test)]
+/// A selector which is registered the first time it is used, and then kept for the rest
+/// of the process. `selector!` declares one per call site.
+pub struct CachedSel {
+    sel: AtomicPtr<c_void>,
+}
+
+impl CachedSel {
+    pub const fn new() -> CachedSel {
+        CachedSel { sel: AtomicPtr::new(ptr::null_mut()) }
+    }
+
+    /// Returns the selector named `name_with_nul`, registering it if this is the first
+    /// call. Registering a name always returns the same selector, so a race with another
+    /// thread is harmless.
+    #[inline]
+    pub fn get(&self, name_with_nul: &str) -> SEL {
+        let sel = self.sel.load(Ordering::Acquire);
+        if !sel.is_null() {
+            return unsafe { mem::transmute::<*mut c_void, SEL>(sel) };
+        }
+        assert!(name_with_nul.ends_with('\0'), "selector names must be nul-terminated");
+        let sel = unsafe { runtime::sel_registerName(name_with_nul.as_ptr() as *const c_char) };
+        self.sel.store(unsafe { mem::transmute::<SEL, *mut c_void>(sel) }, Ordering::Release);
+        sel
+    }
+}
+
+impl Default for CachedSel {
+    fn default() -> CachedSel {
+        CachedSel::new()
+    }
+}
+
+// These need the Objective-C runtime and AppKit.
+#[cfg(all(test, target_os = "macos"))]
 mod test {
     use super::*;
 
     #[test]
     pub fn test_nsapp() {
         unsafe {
-            let _nsApp: id = msg_send![class("NSApplication"), sharedApplication];
+            let _nsApp: id = msg_send![class!(NSApplication), sharedApplication];
+        }
+    }
+
+    #[test]
+    pub fn test_try_class() {
+        assert_eq!(try_class("NSObject").unwrap(), class("NSObject"));
+        let err = try_class("NSNoSuchClass").unwrap_err();
+        assert_eq!(err.name(), "NSNoSuchClass");
+        assert!(err.to_string().contains("`NSNoSuchClass` is not loaded"));
+        assert_eq!(class("NSNoSuchClass"), Nil);
+    }
+
+    #[test]
+    pub fn test_cached_class() {
+        fn object_class() -> Class {
+            class!(NSObject)
+        }
+        assert!(!object_class().is_null());
+        assert_eq!(object_class(), object_class());
+        assert_eq!(object_class(), class("NSObject"));
+
+        static MISSING: CachedClass = CachedClass::new();
+        assert!(MISSING.try_get("NSNoSuchClass").is_err());
+        assert!(MISSING.try_get("NSObject").is_ok());
+    }
+
+    #[test]
+    pub fn test_cached_selector() {
+        fn terminate() -> SEL {
+            selector!(terminate:)
         }
+        assert_eq!(terminate(), selector("terminate:"));
+        assert_eq!(terminate(), terminate());
+        assert_eq!(selector!(description), selector("description"));
+        assert_eq!(selector!(setObject:forKey:).name(), "setObject:forKey:");
     }
 }
@@ -0,0 +1,212 @@
+// Copyright 2013 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversions between Cocoa's coordinate spaces and a top-left origin, y-down space.
+//!
+//! Cocoa uses three spaces that matter here:
+//!
+//! * *Window* coordinates, as returned by `NSEvent::locationInWindow`, have their origin
+//!   at the bottom-left corner of the window frame and y pointing up.
+//! * *Screen* coordinates, as used by `NSWindow::frame`, `NSScreen::frame` and
+//!   `NSEvent::mouseLocation`, are global: their origin is the bottom-left corner of the
+//!   primary screen (the first one in `NSScreen::screens`) and y points up. Other
+//!   screens are placed around it, possibly at negative coordinates.
+//! * *Flipped* coordinates are what most other toolkits and renderers use: the same
+//!   global space, but with the origin at the top-left corner of the primary screen and
+//!   y pointing down.
+//!
+//! Flipping only depends on the height of the primary screen, so the functions here
+//! take a `ScreenLayout` describing the screen frames instead of querying AppKit.
+
+use appkit::NSScreen;
+use base::{id, nil};
+use foundation::{CGFloat, NSPoint, NSRect, NSUInteger};
+
+/// Flips a point between screen coordinates and flipped coordinates, given the height
+/// of the primary screen. The conversion is its own inverse.
+#[inline]
+pub fn flip_point(point: NSPoint, primary_height: CGFloat) -> NSPoint {
+    NSPoint::new(point.x, primary_height - point.y)
+}
+
+/// Flips a rect between screen coordinates and flipped coordinates, given the height of
+/// the primary screen. The conversion is its own inverse.
+///
+/// The rect keeps its size; its origin moves to the opposite vertical edge, so the
+/// bottom-left corner in screen coordinates becomes the top-left corner when flipped.
+#[inline]
+pub fn flip_rect(rect: NSRect, primary_height: CGFloat) -> NSRect {
+    NSRect::new(NSPoint::new(rect.origin.x, primary_height - rect.origin.y - rect.size.height),
+                rect.size)
+}
+
+/// Converts a point in a window's coordinates to screen coordinates, given the window's
+/// frame. This is what `NSWindow::convertRectToScreen_` does to a rect's origin.
+#[inline]
+pub fn window_to_screen_point(point: NSPoint, window_frame: NSRect) -> NSPoint {
+    NSPoint::new(window_frame.origin.x + point.x, window_frame.origin.y + point.y)
+}
+
+/// Converts a point in screen coordinates to a window's coordinates, given the window's
+/// frame.
+#[inline]
+pub fn screen_to_window_point(point: NSPoint, window_frame: NSRect) -> NSPoint {
+    NSPoint::new(point.x - window_frame.origin.x, point.y - window_frame.origin.y)
+}
+
+/// Converts a rect in a window's coordinates to screen coordinates, given the window's
+/// frame, like `NSWindow::convertRectToScreen_`.
+#[inline]
+pub fn window_to_screen_rect(rect: NSRect, window_frame: NSRect) -> NSRect {
+    NSRect::new(window_to_screen_point(rect.origin, window_frame), rect.size)
+}
+
+/// Converts a rect in screen coordinates to a window's coordinates, given the window's
+/// frame, like `NSWindow::convertRectFromScreen_`.
+#[inline]
+pub fn screen_to_window_rect(rect: NSRect, window_frame: NSRect) -> NSRect {
+    NSRect::new(screen_to_window_point(rect.origin, window_frame), rect.size)
+}
+
+/// The arrangement of the attached screens, as a list of screen frames in screen
+/// coordinates. The first frame is the primary screen, which defines the origin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreenLayout {
+    screens: Vec<NSRect>,
+}
+
+impl ScreenLayout {
+    /// Creates a layout from screen frames in the order of `NSScreen::screens`.
+    ///
+    /// Returns `None` if `screens` is empty, since there is no primary screen.
+    pub fn new(screens: Vec<NSRect>) -> Option<ScreenLayout> {
+        if screens.is_empty() {
+            return None;
+        }
+        Some(ScreenLayout {
+            screens: screens,
+        })
+    }
+
+    /// Reads the current layout from `NSScreen::screens`.
+    ///
+    /// The layout is a snapshot; it must be fetched again after
+    /// `NSApplicationDidChangeScreenParametersNotification`. Returns `None` when no
+    /// screens are attached, which can happen briefly while displays are reconfigured or
+    /// on a headless machine.
+    pub unsafe fn current() -> Option<ScreenLayout> {
+        let screens = NSScreen::screens(nil);
+        let count: NSUInteger = msg_send![screens, count];
+        let frames = (0..count).map(|index| {
+            let screen: id = msg_send![screens, objectAtIndex:index];
+            screen.frame()
+        }).collect();
+        ScreenLayout::new(frames)
+    }
+
+    /// The screen frames, in screen coordinates.
+    #[inline]
+    pub fn screens(&self) -> &[NSRect] {
+        &self.screens
+    }
+
+    /// The frame of the primary screen, which always has its origin at (0, 0).
+    #[inline]
+    pub fn primary(&self) -> NSRect {
+        self.screens[0]
+    }
+
+    /// The height of the primary screen, which is the distance between the origins of
+    /// screen coordinates and flipped coordinates.
+    #[inline]
+    pub fn primary_height(&self) -> CGFloat {
+        self.screens[0].size.height
+    }
+
+    /// Returns the frames of the screens in flipped coordinates.
+    pub fn flipped_screens(&self) -> Vec<NSRect> {
+        self.screens.iter().map(|&screen| self.screen_to_flipped_rect(screen)).collect()
+    }
+
+    /// Converts a point from screen coordinates to flipped coordinates.
+    #[inline]
+    pub fn screen_to_flipped_point(&self, point: NSPoint) -> NSPoint {
+        flip_point(point, self.primary_height())
+    }
+
+    /// Converts a point from flipped coordinates to screen coordinates.
+    #[inline]
+    pub fn flipped_to_screen_point(&self, point: NSPoint) -> NSPoint {
+        flip_point(point, self.primary_height())
+    }
+
+    /// Converts a rect from screen coordinates to flipped coordinates.
+    #[inline]
+    pub fn screen_to_flipped_rect(&self, rect: NSRect) -> NSRect {
+        flip_rect(rect, self.primary_height())
+    }
+
+    /// Converts a rect from flipped coordinates to screen coordinates.
+    #[inline]
+    pub fn flipped_to_screen_rect(&self, rect: NSRect) -> NSRect {
+        flip_rect(rect, self.primary_height())
+    }
+
+    /// Converts a point in a window's coordinates to flipped coordinates, given the
+    /// window's frame in screen coordinates.
+    #[inline]
+    pub fn window_to_flipped_point(&self, point: NSPoint, window_frame: NSRect) -> NSPoint {
+        self.screen_to_flipped_point(window_to_screen_point(point, window_frame))
+    }
+
+    /// Converts a point in flipped coordinates to a window's coordinates, given the
+    /// window's frame in screen coordinates.
+    #[inline]
+    pub fn flipped_to_window_point(&self, point: NSPoint, window_frame: NSRect) -> NSPoint {
+        screen_to_window_point(self.flipped_to_screen_point(point), window_frame)
+    }
+
+    /// Converts a rect in a window's coordinates to flipped coordinates, given the
+    /// window's frame in screen coordinates.
+    #[inline]
+    pub fn window_to_flipped_rect(&self, rect: NSRect, window_frame: NSRect) -> NSRect {
+        self.screen_to_flipped_rect(window_to_screen_rect(rect, window_frame))
+    }
+
+    /// Converts a rect in flipped coordinates to a window's coordinates, given the
+    /// window's frame in screen coordinates.
+    #[inline]
+    pub fn flipped_to_window_rect(&self, rect: NSRect, window_frame: NSRect) -> NSRect {
+        screen_to_window_rect(self.flipped_to_screen_rect(rect), window_frame)
+    }
+
+    /// Returns the index of the screen containing `point`, given in screen coordinates.
+    ///
+    /// Like the mouse location, a point on the top edge of a screen belongs to it and a
+    /// point on its bottom edge does not.
+    pub fn screen_at_point(&self, point: NSPoint) -> Option<usize> {
+        self.screens.iter().position(|screen| screen.mouse_in_rect(&point, false))
+    }
+
+    /// Returns the index of the screen which shows the largest part of `rect`, given in
+    /// screen coordinates, the way AppKit picks `NSWindow::screen`.
+    pub fn screen_for_rect(&self, rect: NSRect) -> Option<usize> {
+        let mut best = None;
+        let mut best_area = 0.;
+        for (index, screen) in self.screens.iter().enumerate() {
+            let overlap = screen.intersection(&rect);
+            let area = overlap.size.width * overlap.size.height;
+            if area > best_area {
+                best = Some(index);
+                best_area = area;
+            }
+        }
+        best
+    }
+}
@@ -0,0 +1,732 @@
+// Copyright 2013 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Objective-C type encodings.
+//!
+//! `objc::Encoding` only holds an encoding string such as `{CGPoint=dd}`. This module
+//! parses those strings into an `Encoding` tree, and method type encodings such as
+//! `v24@0:8@16` into a `MethodSignature`, so that the types a method is declared with
+//! can be inspected and compared with the types we pass through `msg_send!`.
+
+use objc::{self, Message, MessageArguments};
+use objc::runtime::{self, Class, Method, Object, Sel};
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A type qualifier, which precedes the type it applies to in an encoding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Qualifier {
+    /// `r`
+    Const,
+    /// `n`
+    In,
+    /// `N`
+    InOut,
+    /// `o`
+    Out,
+    /// `O`
+    ByCopy,
+    /// `R`
+    ByRef,
+    /// `V`
+    OneWay,
+}
+
+impl Qualifier {
+    fn from_code(code: u8) -> Option<Qualifier> {
+        match code {
+            b'r' => Some(Qualifier::Const),
+            b'n' => Some(Qualifier::In),
+            b'N' => Some(Qualifier::InOut),
+            b'o' => Some(Qualifier::Out),
+            b'O' => Some(Qualifier::ByCopy),
+            b'R' => Some(Qualifier::ByRef),
+            b'V' => Some(Qualifier::OneWay),
+            _ => None,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match *self {
+            Qualifier::Const => "r",
+            Qualifier::In => "n",
+            Qualifier::InOut => "N",
+            Qualifier::Out => "o",
+            Qualifier::ByCopy => "O",
+            Qualifier::ByRef => "R",
+            Qualifier::OneWay => "V",
+        }
+    }
+}
+
+/// A parsed Objective-C type encoding.
+///
+/// `Display` writes the encoding string back out, and `FromStr` parses one complete
+/// type; trailing characters are an error.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// `c`, which is also `BOOL` on x86_64.
+    Char,
+    /// `s`
+    Short,
+    /// `i`
+    Int,
+    /// `l`, a 32-bit `long`. 64-bit `long`s are encoded as `q`.
+    Long,
+    /// `q`
+    LongLong,
+    /// `C`
+    UChar,
+    /// `S`
+    UShort,
+    /// `I`
+    UInt,
+    /// `L`, a 32-bit `unsigned long`. 64-bit ones are encoded as `Q`.
+    ULong,
+    /// `Q`
+    ULongLong,
+    /// `f`
+    Float,
+    /// `d`
+    Double,
+    /// `D`
+    LongDouble,
+    /// `B`, a C++ `bool` or C99 `_Bool`.
+    Bool,
+    /// `v`
+    Void,
+    /// `*`, a C string.
+    String,
+    /// `@`, an object, with its class name if the encoding gives one, as in
+    /// `@"NSString"`.
+    Object(Option<String>),
+    /// `@?`, a block. The block's signature, as in `@?<v@?@"NSData">`, is parsed but not
+    /// kept.
+    Block,
+    /// `#`
+    Class,
+    /// `:`
+    Sel,
+    /// `?`, an unknown type such as a function.
+    Unknown,
+    /// `b` followed by the width of the bit field.
+    BitField(u32),
+    /// `^` followed by the pointee.
+    Pointer(Box<Encoding>),
+    /// `[` count, element type `]`.
+    Array(usize, Box<Encoding>),
+    /// `{name=fields}`. The fields are empty when the encoding only names the struct,
+    /// as in `^{CGRect}`, and the name is `?` for anonymous structs. Field names, as in
+    /// `{CGPoint="x"d"y"d}`, are parsed but not kept.
+    Struct(String, Vec<Encoding>),
+    /// `(name=fields)`, with the same conventions as `Struct`.
+    Union(String, Vec<Encoding>),
+    /// A qualifier followed by the type it applies to.
+    Qualified(Qualifier, Box<Encoding>),
+}
+
+impl Encoding {
+    /// Returns the parsed encoding of `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `objc::Encode` implementation of `T` returns an invalid encoding.
+    pub fn of<T: objc::Encode>() -> Encoding {
+        parse_objc_encoding(T::encode())
+    }
+
+    /// Returns the encoding without any qualifiers in front of it.
+    pub fn unqualified(&self) -> &Encoding {
+        match *self {
+            Encoding::Qualified(_, ref encoding) => encoding.unqualified(),
+            _ => self,
+        }
+    }
+
+    /// Returns whether `self` and `other` describe the same type.
+    ///
+    /// Unlike `==`, this ignores qualifiers and the class names of objects, and a struct
+    /// or union that is only named matches any fields. Anonymous structs and unions
+    /// match any name.
+    pub fn is_equivalent(&self, other: &Encoding) -> bool {
+        match (self.unqualified(), other.unqualified()) {
+            (&Encoding::Object(_), &Encoding::Object(_)) => true,
+            (&Encoding::Pointer(ref a), &Encoding::Pointer(ref b)) => a.is_equivalent(b),
+            (&Encoding::Array(len_a, ref a), &Encoding::Array(len_b, ref b)) => {
+                len_a == len_b && a.is_equivalent(b)
+            }
+            (&Encoding::Struct(ref name_a, ref a), &Encoding::Struct(ref name_b, ref b)) |
+            (&Encoding::Union(ref name_a, ref a), &Encoding::Union(ref name_b, ref b)) => {
+                (name_a == name_b || name_a == "?" || name_b == "?") &&
+                (a.is_empty() || b.is_empty() ||
+                 (a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is_equivalent(b))))
+            }
+            (a, b) => a == b,
+        }
+    }
+}
+
+fn parse_objc_encoding(encoding: objc::Encoding) -> Encoding {
+    match encoding.as_str().parse() {
+        Ok(parsed) => parsed,
+        Err(err) => panic!("{:?} is not a valid type encoding: {}", encoding, err),
+    }
+}
+
+impl PartialEq<objc::Encoding> for Encoding {
+    /// Compares with the encoding of a Rust type using `is_equivalent`.
+    fn eq(&self, other: &objc::Encoding) -> bool {
+        match other.as_str().parse::<Encoding>() {
+            Ok(other) => self.is_equivalent(&other),
+            Err(_) => false,
+        }
+    }
+}
+
+impl PartialEq<Encoding> for objc::Encoding {
+    fn eq(&self, other: &Encoding) -> bool {
+        other == self
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let code = match *self {
+            Encoding::Char => "c",
+            Encoding::Short => "s",
+            Encoding::Int => "i",
+            Encoding::Long => "l",
+            Encoding::LongLong => "q",
+            Encoding::UChar => "C",
+            Encoding::UShort => "S",
+            Encoding::UInt => "I",
+            Encoding::ULong => "L",
+            Encoding::ULongLong => "Q",
+            Encoding::Float => "f",
+            Encoding::Double => "d",
+            Encoding::LongDouble => "D",
+            Encoding::Bool => "B",
+            Encoding::Void => "v",
+            Encoding::String => "*",
+            Encoding::Object(None) => "@",
+            Encoding::Block => "@?",
+            Encoding::Class => "#",
+            Encoding::Sel => ":",
+            Encoding::Unknown => "?",
+            Encoding::Object(Some(ref class)) => return write!(f, "@\"{}\"", class),
+            Encoding::BitField(width) => return write!(f, "b{}", width),
+            Encoding::Pointer(ref pointee) => return write!(f, "^{}", pointee),
+            Encoding::Array(len, ref element) => return write!(f, "[{}{}]", len, element),
+            Encoding::Struct(ref name, ref fields) => {
+                return write_aggregate(f, "{", name, fields, "}");
+            }
+            Encoding::Union(ref name, ref fields) => {
+                return write_aggregate(f, "(", name, fields, ")");
+            }
+            Encoding::Qualified(qualifier, ref encoding) => {
+                return write!(f, "{}{}", qualifier.code(), encoding);
+            }
+        };
+        f.write_str(code)
+    }
+}
+
+fn write_aggregate(f: &mut fmt::Formatter, open: &str, name: &str, fields: &[Encoding],
+                   close: &str) -> fmt::Result {
+    f.write_str(open)?;
+    f.write_str(name)?;
+    if !fields.is_empty() {
+        f.write_str("=")?;
+        for field in fields {
+            write!(f, "{}", field)?;
+        }
+    }
+    f.write_str(close)
+}
+
+impl FromStr for Encoding {
+    type Err = ParseEncodingError;
+
+    fn from_str(s: &str) -> Result<Encoding, ParseEncodingError> {
+        let mut parser = Parser::new(s);
+        let encoding = parser.parse_encoding()?;
+        parser.expect_end()?;
+        Ok(encoding)
+    }
+}
+
+/// An argument in a `MethodSignature`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Argument {
+    pub encoding: Encoding,
+    /// The offset of the argument in the argument frame, if the signature gives one.
+    pub offset: Option<usize>,
+}
+
+/// A parsed method type encoding, such as `v24@0:8@16` for `- (void)setTitle:(id)title`.
+///
+/// The arguments include the receiver and the selector, which every method takes
+/// first.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MethodSignature {
+    pub return_type: Encoding,
+    /// The size of the argument frame, if the signature gives one.
+    pub frame_size: Option<usize>,
+    pub arguments: Vec<Argument>,
+}
+
+impl MethodSignature {
+    /// Reads the signature of a method from the runtime. The runtime does not give
+    /// offsets this way, so they are all `None`.
+    pub fn from_method(method: &Method) -> Result<MethodSignature, ParseEncodingError> {
+        let return_type = method.return_type().as_str().parse()?;
+        let mut arguments = Vec::with_capacity(method.arguments_count());
+        for index in 0..method.arguments_count() {
+            if let Some(encoding) = method.argument_type(index) {
+                arguments.push(Argument {
+                    encoding: encoding.as_str().parse()?,
+                    offset: None,
+                });
+            }
+        }
+        Ok(MethodSignature {
+            return_type: return_type,
+            frame_size: None,
+            arguments: arguments,
+        })
+    }
+
+    /// The signature of a message sent from Rust with arguments of the types in `A` and
+    /// a return value of type `R`. The receiver is encoded as an object.
+    pub fn of<A: EncodeArguments, R: objc::Encode>() -> MethodSignature {
+        let mut arguments = vec![
+            Argument { encoding: Encoding::Object(None), offset: None },
+            Argument { encoding: Encoding::Sel, offset: None },
+        ];
+        arguments.extend(A::encodings().into_iter().map(|encoding| {
+            Argument {
+                encoding: parse_objc_encoding(encoding),
+                offset: None,
+            }
+        }));
+        MethodSignature {
+            return_type: Encoding::of::<R>(),
+            frame_size: None,
+            arguments: arguments,
+        }
+    }
+
+    /// The arguments after the receiver and the selector, which are the ones passed
+    /// explicitly to `msg_send!`.
+    pub fn explicit_arguments(&self) -> &[Argument] {
+        if self.arguments.len() > 2 {
+            &self.arguments[2..]
+        } else {
+            &[]
+        }
+    }
+
+    /// Returns whether a message with the argument and return types of `passed` can be
+    /// sent to a method declared with this signature.
+    ///
+    /// The types must be equivalent, with a few exceptions that are safe at the ABI
+    /// level: integers of the same width but different signedness are interchangeable,
+    /// as are classes and objects, an untyped pointer (`^v`) may be passed for any
+    /// pointer, and a void return may be expected from any method that doesn't return
+    /// a struct or union.
+    pub fn accepts(&self, passed: &MethodSignature) -> bool {
+        let declared_return = self.return_type.unqualified();
+        let return_type = match (declared_return, passed.return_type.unqualified()) {
+            (&Encoding::Struct(..), &Encoding::Void) |
+            (&Encoding::Union(..), &Encoding::Void) => false,
+            (_, &Encoding::Void) => true,
+            (declared, passed) => accepts_value(declared, passed),
+        };
+        return_type && self.arguments.len() == passed.arguments.len() &&
+            self.arguments.iter().zip(&passed.arguments).all(|(declared, passed)| {
+                accepts_value(&declared.encoding, &passed.encoding)
+            })
+    }
+}
+
+fn accepts_value(declared: &Encoding, passed: &Encoding) -> bool {
+    match (declared.unqualified(), passed.unqualified()) {
+        (&Encoding::Object(_), &Encoding::Class) |
+        (&Encoding::Class, &Encoding::Object(_)) => true,
+        (&Encoding::Pointer(_), &Encoding::Pointer(ref pointee)) |
+        (&Encoding::String, &Encoding::Pointer(ref pointee))
+            if *pointee.unqualified() == Encoding::Void => true,
+        (declared, passed) => {
+            declared.is_equivalent(passed) ||
+            (integer_width(declared).is_some() && integer_width(declared) == integer_width(passed))
+        }
+    }
+}
+
+fn integer_width(encoding: &Encoding) -> Option<usize> {
+    match *encoding {
+        Encoding::Char | Encoding::UChar => Some(1),
+        Encoding::Short | Encoding::UShort => Some(2),
+        Encoding::Int | Encoding::UInt | Encoding::Long | Encoding::ULong => Some(4),
+        Encoding::LongLong | Encoding::ULongLong => Some(8),
+        _ => None,
+    }
+}
+
+impl fmt::Display for MethodSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.return_type)?;
+        if let Some(frame_size) = self.frame_size {
+            write!(f, "{}", frame_size)?;
+        }
+        for argument in &self.arguments {
+            write!(f, "{}", argument.encoding)?;
+            if let Some(offset) = argument.offset {
+                write!(f, "{}", offset)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MethodSignature {
+    type Err = ParseEncodingError;
+
+    fn from_str(s: &str) -> Result<MethodSignature, ParseEncodingError> {
+        let mut parser = Parser::new(s);
+        let return_type = parser.parse_encoding()?;
+        let frame_size = parser.parse_number()?;
+        let mut arguments = Vec::new();
+        while !parser.at_end() {
+            let encoding = parser.parse_encoding()?;
+            let offset = parser.parse_number()?;
+            arguments.push(Argument {
+                encoding: encoding,
+                offset: offset,
+            });
+        }
+        Ok(MethodSignature {
+            return_type: return_type,
+            frame_size: frame_size,
+            arguments: arguments,
+        })
+    }
+}
+
+/// The argument types of a message, as the tuple passed to `objc::MessageArguments`.
+pub trait EncodeArguments {
+    fn encodings() -> Vec<objc::Encoding>;
+}
+
+macro_rules! encode_arguments_impl {
+    ($($t:ident),*) => (
+        impl<$($t: objc::Encode),*> EncodeArguments for ($($t,)*) {
+            fn encodings() -> Vec<objc::Encoding> {
+                vec![$($t::encode()),*]
+            }
+        }
+    );
+}
+
+encode_arguments_impl!();
+encode_arguments_impl!(A);
+encode_arguments_impl!(A, B);
+encode_arguments_impl!(A, B, C);
+encode_arguments_impl!(A, B, C, D);
+encode_arguments_impl!(A, B, C, D, E);
+encode_arguments_impl!(A, B, C, D, E, F);
+encode_arguments_impl!(A, B, C, D, E, F, G);
+encode_arguments_impl!(A, B, C, D, E, F, G, H);
+encode_arguments_impl!(A, B, C, D, E, F, G, H, I);
+encode_arguments_impl!(A, B, C, D, E, F, G, H, I, J);
+encode_arguments_impl!(A, B, C, D, E, F, G, H, I, J, K);
+encode_arguments_impl!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// Checks that a message with arguments of the types in `A` and a return value of type
+/// `R` matches the signature `obj` declares for `sel`, using `MethodSignature::accepts`.
+///
+/// Messages to nil, and messages `obj` has no method for and may forward, are not
+/// checked.
+///
+/// # Panics
+///
+/// Panics with the class, the selector and both signatures if they don't match.
+pub unsafe fn verify_message<A, R>(obj: *const Object, sel: Sel)
+        where A: EncodeArguments, R: objc::Encode {
+    if obj.is_null() {
+        return;
+    }
+    let class = &*runtime::object_getClass(obj);
+    let method = match class.instance_method(sel) {
+        Some(method) => method,
+        None => return,
+    };
+    let declared = match MethodSignature::from_method(method) {
+        Ok(declared) => declared,
+        Err(_) => return,
+    };
+    let passed = MethodSignature::of::<A, R>();
+    if !declared.accepts(&passed) {
+        // A class object has a metaclass of the same name as its class.
+        let is_class = Class::get(class.name()).is_some_and(|named| {
+            named as *const Class as *const Object == obj
+        });
+        panic!("invalid message send to {}[{} {}]: expected {}, found {}",
+               if is_class { "+" } else { "-" }, class.name(), sel.name(), declared, passed);
+    }
+}
+
+/// Sends a message after checking it with `verify_message`. With the `verify_message`
+/// feature, this is what `msg_send!` expands to in this crate.
+pub unsafe fn send_verified<T, A, R>(obj: *const T, sel: Sel, args: A) -> R
+        where T: Message, A: MessageArguments + EncodeArguments, R: Any + objc::Encode {
+    verify_message::<A, R>(obj as *const Object, sel);
+    args.send(obj as *mut T, sel)
+}
+
+/// The error returned when parsing an invalid `Encoding` or `MethodSignature`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseEncodingError {
+    position: usize,
+    message: &'static str,
+}
+
+impl ParseEncodingError {
+    /// The byte offset in the input at which parsing failed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for ParseEncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.position)
+    }
+}
+
+impl Error for ParseEncodingError {}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    position: usize,
+    // Whether the fields of the innermost struct or union being parsed are named.
+    named_fields: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Parser<'a> {
+        Parser {
+            input: input.as_bytes(),
+            position: 0,
+            named_fields: false,
+        }
+    }
+
+    fn error(&self, message: &'static str) -> ParseEncodingError {
+        ParseEncodingError {
+            position: self.position,
+            message: message,
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.position == self.input.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).cloned()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_end(&self) -> Result<(), ParseEncodingError> {
+        if self.at_end() {
+            Ok(())
+        } else {
+            Err(self.error("unexpected trailing characters"))
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Option<usize>, ParseEncodingError> {
+        let start = self.position;
+        let mut value: usize = 0;
+        while let Some(digit) = self.peek() {
+            if !digit.is_ascii_digit() {
+                break;
+            }
+            let next = value.checked_mul(10).and_then(|v| v.checked_add((digit - b'0') as usize));
+            value = match next {
+                Some(value) => value,
+                None => {
+                    self.position = start;
+                    return Err(self.error("number is too large"));
+                }
+            };
+            self.position += 1;
+        }
+        Ok(if self.position > start { Some(value) } else { None })
+    }
+
+    fn parse_required_number(&mut self) -> Result<usize, ParseEncodingError> {
+        match self.parse_number()? {
+            Some(value) => Ok(value),
+            None => Err(self.error("expected a number")),
+        }
+    }
+
+    // Reads up to, but not including, the first of `terminators`.
+    fn parse_name(&mut self, terminators: &[u8]) -> Result<String, ParseEncodingError> {
+        let start = self.position;
+        loop {
+            match self.peek() {
+                Some(byte) if terminators.contains(&byte) => break,
+                Some(_) => self.position += 1,
+                None => return Err(self.error("unexpected end of encoding")),
+            }
+        }
+        Ok(String::from_utf8_lossy(&self.input[start..self.position]).into_owned())
+    }
+
+    fn parse_encoding(&mut self) -> Result<Encoding, ParseEncodingError> {
+        let code = match self.peek() {
+            Some(code) => code,
+            None => return Err(self.error("unexpected end of encoding")),
+        };
+        if let Some(qualifier) = Qualifier::from_code(code) {
+            self.position += 1;
+            return Ok(Encoding::Qualified(qualifier, Box::new(self.parse_encoding()?)));
+        }
+        self.position += 1;
+        let encoding = match code {
+            b'c' => Encoding::Char,
+            b's' => Encoding::Short,
+            b'i' => Encoding::Int,
+            b'l' => Encoding::Long,
+            b'q' => Encoding::LongLong,
+            b'C' => Encoding::UChar,
+            b'S' => Encoding::UShort,
+            b'I' => Encoding::UInt,
+            b'L' => Encoding::ULong,
+            b'Q' => Encoding::ULongLong,
+            b'f' => Encoding::Float,
+            b'd' => Encoding::Double,
+            b'D' => Encoding::LongDouble,
+            b'B' => Encoding::Bool,
+            b'v' => Encoding::Void,
+            b'*' => Encoding::String,
+            b'#' => Encoding::Class,
+            b':' => Encoding::Sel,
+            b'?' => Encoding::Unknown,
+            b'@' => {
+                if self.eat(b'?') {
+                    if self.eat(b'<') {
+                        self.parse_block_signature()?;
+                    }
+                    Encoding::Block
+                } else if self.peek() == Some(b'"') && self.quotes_class_name()? {
+                    self.position += 1;
+                    let class = self.parse_name(b"\"")?;
+                    self.position += 1;
+                    Encoding::Object(Some(class))
+                } else {
+                    Encoding::Object(None)
+                }
+            }
+            b'b' => {
+                let width = self.parse_required_number()?;
+                if width > u32::MAX as usize {
+                    return Err(self.error("bit field is too wide"));
+                }
+                Encoding::BitField(width as u32)
+            }
+            b'^' => Encoding::Pointer(Box::new(self.parse_encoding()?)),
+            b'[' => {
+                let len = self.parse_required_number()?;
+                let element = self.parse_encoding()?;
+                if !self.eat(b']') {
+                    return Err(self.error("expected `]`"));
+                }
+                Encoding::Array(len, Box::new(element))
+            }
+            b'{' => {
+                let (name, fields) = self.parse_aggregate(b'}')?;
+                Encoding::Struct(name, fields)
+            }
+            b'(' => {
+                let (name, fields) = self.parse_aggregate(b')')?;
+                Encoding::Union(name, fields)
+            }
+            _ => {
+                self.position -= 1;
+                return Err(self.error("unknown type code"));
+            }
+        };
+        Ok(encoding)
+    }
+
+    fn parse_aggregate(&mut self, close: u8)
+                       -> Result<(String, Vec<Encoding>), ParseEncodingError> {
+        let name = self.parse_name(&[b'=', close])?;
+        let mut fields = Vec::new();
+        if self.eat(b'=') {
+            let outer_named_fields = self.named_fields;
+            self.named_fields = self.peek() == Some(b'"');
+            while !self.eat(close) {
+                if self.named_fields {
+                    if !self.eat(b'"') {
+                        return Err(self.error("expected a field name"));
+                    }
+                    self.parse_name(b"\"")?;
+                    self.position += 1;
+                }
+                fields.push(self.parse_encoding()?);
+            }
+            self.named_fields = outer_named_fields;
+        } else {
+            self.position += 1;
+        }
+        Ok((name, fields))
+    }
+
+    // With named fields, `@"name"` is either an object with a class name or an object
+    // followed by the next field's name. A class name is followed by another field name
+    // or the end of the aggregate; a field name is followed by the field's type.
+    fn quotes_class_name(&self) -> Result<bool, ParseEncodingError> {
+        if !self.named_fields {
+            return Ok(true);
+        }
+        let start = self.position + 1;
+        match self.input[start..].iter().position(|&byte| byte == b'"') {
+            Some(len) => Ok(matches!(self.input.get(start + len + 1),
+                                     Some(&b'"') | Some(&b'}') | Some(&b')'))),
+            None => Err(self.error("unexpected end of encoding")),
+        }
+    }
+
+    // Reads a block's signature up to and including its closing `>`. It is the return
+    // type, the block itself and the arguments, without offsets.
+    fn parse_block_signature(&mut self) -> Result<(), ParseEncodingError> {
+        let outer_named_fields = self.named_fields;
+        self.named_fields = false;
+        while !self.eat(b'>') {
+            self.parse_encoding()?;
+        }
+        self.named_fields = outer_named_fields;
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright 2013 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Catching Objective-C exceptions, with the `exception` feature.
+//!
+//! An exception thrown by AppKit or Foundation can't unwind through Rust frames, and
+//! aborts the process. `catch_exception` runs a closure inside `@try`, so that a binding
+//! call which throws returns an `NSExceptionInfo` instead. `try_msg_send!` does the same
+//! for a single message.
+//!
+//! The bindings have no checked variants of their own: wrap the calls which may throw in
+//! `catch_exception`, or send the message with `try_msg_send!`.
+
+use base::{id, nil, BOOL, NO};
+#[cfg(feature = "verify_message")]
+use encoding::{self, EncodeArguments};
+use foundation::{NSException, NSExceptionRef, NSString};
+use object::ObjCClass;
+use objc::{self, Message, MessageArguments};
+use objc::runtime::{Object, Sel};
+use objc_exception;
+use rc::StrongPtr;
+use std::any::Any;
+use std::error::Error;
+use std::ffi::CStr;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+/// An Objective-C exception caught by `catch_exception`.
+#[derive(Clone, Debug)]
+pub struct NSExceptionInfo {
+    /// The exception's name, such as `NSRangeException`. Objects thrown which aren't an
+    /// `NSException` are named after their class.
+    pub name: String,
+    /// Why the exception was thrown, or, if it isn't an `NSException`, its description.
+    pub reason: Option<String>,
+    /// The exception's `userInfo` dictionary, which may be nil.
+    pub user_info: StrongPtr,
+    /// The object that was thrown, which may be nil.
+    pub exception: StrongPtr,
+}
+
+impl NSExceptionInfo {
+    /// Takes ownership of a retained exception.
+    unsafe fn new(exception: id) -> NSExceptionInfo {
+        let exception = StrongPtr::new(exception);
+        if exception.is_null() {
+            return NSExceptionInfo {
+                name: "nil".to_owned(),
+                reason: None,
+                user_info: StrongPtr::new(nil),
+                exception: exception,
+            };
+        }
+        let is_exception: BOOL = msg_send![*exception, isKindOfClass:NSExceptionRef::class()];
+        if is_exception != NO {
+            NSExceptionInfo {
+                name: to_string(exception.name()).unwrap_or_default(),
+                reason: to_string(exception.reason()),
+                user_info: StrongPtr::retain(exception.userInfo()),
+                exception: exception,
+            }
+        } else {
+            let description: id = msg_send![*exception, description];
+            NSExceptionInfo {
+                name: (**exception).class().name().to_owned(),
+                reason: to_string(description),
+                user_info: StrongPtr::new(nil),
+                exception: exception,
+            }
+        }
+    }
+}
+
+impl fmt::Display for NSExceptionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.reason {
+            Some(ref reason) => write!(f, "{}: {}", self.name, reason),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl Error for NSExceptionInfo {}
+
+unsafe fn to_string(string: id) -> Option<String> {
+    if string.is_null() {
+        return None;
+    }
+    let bytes = string.UTF8String();
+    if bytes.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(bytes).to_string_lossy().into_owned())
+    }
+}
+
+/// Runs `f` inside an Objective-C `@try`, returning the exception it throws, if any.
+///
+/// A Rust panic in `f` is caught before it reaches the Objective-C frames of the `@try`,
+/// and resumed once they have returned.
+pub unsafe fn catch_exception<F, R>(f: F) -> Result<R, NSExceptionInfo>
+        where F: FnOnce() -> R {
+    match objc_exception::try(|| panic::catch_unwind(AssertUnwindSafe(f))) {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(payload)) => panic::resume_unwind(payload),
+        Err(exception) => Err(NSExceptionInfo::new(exception as *mut Object)),
+    }
+}
+
+/// Sends a message inside `catch_exception`, returning the exception it throws, if any.
+/// This is what `try_msg_send!` expands to.
+#[cfg(not(feature = "verify_message"))]
+pub unsafe fn try_send<T, A, R>(obj: *const T, sel: Sel, args: A) -> Result<R, NSExceptionInfo>
+        where T: Message, A: MessageArguments, R: Any {
+    catch_exception(|| args.send(obj as *mut T, sel))
+}
+
+/// Sends a message inside `catch_exception`, returning the exception it throws, if any.
+/// This is what `try_msg_send!` expands to.
+///
+/// The message is checked with `encoding::verify_message` before entering the `@try`, so
+/// a mismatch panics from outside it.
+#[cfg(feature = "verify_message")]
+pub unsafe fn try_send<T, A, R>(obj: *const T, sel: Sel, args: A) -> Result<R, NSExceptionInfo>
+        where T: Message, A: MessageArguments + EncodeArguments, R: Any + objc::Encode {
+    encoding::verify_message::<A, R>(obj as *const Object, sel);
+    catch_exception(|| args.send(obj as *mut T, sel))
+}
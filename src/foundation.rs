@@ -7,9 +7,40 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use base::{id, class, BOOL, nil};
+use base::{id, BOOL, NO, YES, nil};
+use block::{Block, ConcreteBlock};
+#[cfg(target_os = "macos")]
+use core_graphics::geometry::{CGPoint, CGSize, CGRect};
 use libc;
 use objc;
+use object::{AsId, IsKindOf, ObjCClass};
+use rc::{AutoreleasePool, Autoreleased, ReferenceCounting, Retained, StrongPtr};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::{CStr, CString, OsString};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+use std::ops::Range;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::slice;
+use std::str::FromStr;
+use std::string::ParseError;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(target_os = "macos")]
+pub use core_graphics::base::CGFloat;
+// Core Graphics is only linked on macOS; elsewhere `CGFloat` is defined the same way,
+// so the geometry types can be used and tested without it.
+#[cfg(all(not(target_os = "macos"), target_pointer_width = "64"))]
+pub type CGFloat = libc::c_double;
+#[cfg(all(not(target_os = "macos"), target_pointer_width = "32"))]
+pub type CGFloat = libc::c_float;
 
 #[cfg(target_pointer_width = "32")]
 pub type NSInteger = libc::c_int;
@@ -23,15 +54,34 @@ pub type NSUInteger = libc::c_ulong;
 
 const UTF8_ENCODING: usize = 4;
 
+// The geometry types follow CGFloat, which is a float on 32-bit targets. There the
+// Foundation structs are also distinct from the Core Graphics ones, which shows in
+// their type encodings.
+#[cfg(target_pointer_width = "32")]
+const NSPOINT_STRUCT_NAME: &str = "_NSPoint";
+#[cfg(target_pointer_width = "32")]
+const NSSIZE_STRUCT_NAME: &str = "_NSSize";
+#[cfg(target_pointer_width = "32")]
+const NSRECT_STRUCT_NAME: &str = "_NSRect";
+
+#[cfg(target_pointer_width = "64")]
+const NSPOINT_STRUCT_NAME: &str = "CGPoint";
+#[cfg(target_pointer_width = "64")]
+const NSSIZE_STRUCT_NAME: &str = "CGSize";
+#[cfg(target_pointer_width = "64")]
+const NSRECT_STRUCT_NAME: &str = "CGRect";
+
 #[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NSPoint {
-    pub x: f64,
-    pub y: f64,
+    pub x: CGFloat,
+    pub y: CGFloat,
 }
 
 impl NSPoint {
     #[inline]
-    pub fn new(x: f64, y: f64) -> NSPoint {
+    pub fn new(x: CGFloat, y: CGFloat) -> NSPoint {
         NSPoint {
             x: x,
             y: y,
@@ -41,22 +91,25 @@ impl NSPoint {
 
 unsafe impl objc::Encode for NSPoint {
     fn encode() -> objc::Encoding {
-        let encoding = format!("{{CGPoint={}{}}}",
-                               f64::encode().as_str(),
-                               f64::encode().as_str());
+        let encoding = format!("{{{}={}{}}}",
+                               NSPOINT_STRUCT_NAME,
+                               CGFloat::encode().as_str(),
+                               CGFloat::encode().as_str());
         unsafe { objc::Encoding::from_str(&encoding) }
     }
 }
 
 #[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NSSize {
-    pub width: f64,
-    pub height: f64,
+    pub width: CGFloat,
+    pub height: CGFloat,
 }
 
 impl NSSize {
     #[inline]
-    pub fn new(width: f64, height: f64) -> NSSize {
+    pub fn new(width: CGFloat, height: CGFloat) -> NSSize {
         NSSize {
             width: width,
             height: height,
@@ -66,14 +119,17 @@ impl NSSize {
 
 unsafe impl objc::Encode for NSSize {
     fn encode() -> objc::Encoding {
-        let encoding = format!("{{CGSize={}{}}}",
-                               f64::encode().as_str(),
-                               f64::encode().as_str());
+        let encoding = format!("{{{}={}{}}}",
+                               NSSIZE_STRUCT_NAME,
+                               CGFloat::encode().as_str(),
+                               CGFloat::encode().as_str());
         unsafe { objc::Encoding::from_str(&encoding) }
     }
 }
 
 #[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NSRect {
     pub origin: NSPoint,
     pub size: NSSize,
@@ -87,9 +143,216 @@ impl NSRect {
             size: size
         }
     }
+
+    #[inline]
+    fn from_coords(x: CGFloat, y: CGFloat, width: CGFloat, height: CGFloat) -> NSRect {
+        NSRect::new(NSPoint::new(x, y), NSSize::new(width, height))
+    }
+
+    /// A rect with its origin at (0, 0) and zero width and height, like `NSZeroRect`.
+    #[inline]
+    pub fn zero() -> NSRect {
+        NSRect::from_coords(0., 0., 0., 0.)
+    }
+
+    // Querying Rectangle Coordinates
+    //
+    // Like the NSGeometry inline functions, none of these standardize a rect with a
+    // negative size, so `max_x` is smaller than `min_x` when the width is negative.
+
+    #[inline]
+    pub fn min_x(&self) -> CGFloat {
+        self.origin.x
+    }
+
+    #[inline]
+    pub fn mid_x(&self) -> CGFloat {
+        self.origin.x + self.size.width * 0.5
+    }
+
+    #[inline]
+    pub fn max_x(&self) -> CGFloat {
+        self.origin.x + self.size.width
+    }
+
+    #[inline]
+    pub fn min_y(&self) -> CGFloat {
+        self.origin.y
+    }
+
+    #[inline]
+    pub fn mid_y(&self) -> CGFloat {
+        self.origin.y + self.size.height * 0.5
+    }
+
+    #[inline]
+    pub fn max_y(&self) -> CGFloat {
+        self.origin.y + self.size.height
+    }
+
+    #[inline]
+    pub fn width(&self) -> CGFloat {
+        self.size.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> CGFloat {
+        self.size.height
+    }
+
+    // Checking for Membership
+
+    /// Returns true if the width or height is zero, negative or NaN, like `NSIsEmptyRect`.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        !(self.size.width > 0. && self.size.height > 0.)
+    }
+
+    /// Returns true if `point` lies within the rect, like `NSPointInRect`.
+    ///
+    /// The minimum edges are inside the rect and the maximum edges are not, which is
+    /// the convention for a flipped coordinate system.
+    #[inline]
+    pub fn contains_point(&self, point: &NSPoint) -> bool {
+        self.mouse_in_rect(point, true)
+    }
+
+    /// Hit-tests `point` the way `NSMouseInRect` does.
+    ///
+    /// In an unflipped coordinate system the bottom edge is outside the rect and the
+    /// top edge is inside it.
+    pub fn mouse_in_rect(&self, point: &NSPoint, flipped: bool) -> bool {
+        if point.x < self.min_x() || point.x >= self.max_x() {
+            return false;
+        }
+        if flipped {
+            point.y >= self.min_y() && point.y < self.max_y()
+        } else {
+            point.y > self.min_y() && point.y <= self.max_y()
+        }
+    }
+
+    /// Returns true if `other` is non-empty and lies completely within the rect, like
+    /// `NSContainsRect`.
+    pub fn contains_rect(&self, other: &NSRect) -> bool {
+        !other.is_empty() &&
+            self.min_x() <= other.min_x() && other.max_x() <= self.max_x() &&
+            self.min_y() <= other.min_y() && other.max_y() <= self.max_y()
+    }
+
+    /// Returns true if both rects are non-empty and overlap, like `NSIntersectsRect`.
+    ///
+    /// Rects which only share an edge do not intersect.
+    pub fn intersects(&self, other: &NSRect) -> bool {
+        !(self.is_empty() || other.is_empty() ||
+          self.max_x() <= other.min_x() || other.max_x() <= self.min_x() ||
+          self.max_y() <= other.min_y() || other.max_y() <= self.min_y())
+    }
+
+    // Modifying Rectangles
+
+    /// Returns the overlapping area of the two rects, like `NSIntersectionRect`.
+    ///
+    /// If the rects do not intersect the result is `NSRect::zero()`.
+    pub fn intersection(&self, other: &NSRect) -> NSRect {
+        if !self.intersects(other) {
+            return NSRect::zero();
+        }
+        let min_x = self.min_x().max(other.min_x());
+        let min_y = self.min_y().max(other.min_y());
+        let max_x = self.max_x().min(other.max_x());
+        let max_y = self.max_y().min(other.max_y());
+        NSRect::from_coords(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    /// Returns the smallest rect enclosing both rects, like `NSUnionRect`.
+    ///
+    /// An empty rect does not contribute to the union: if one rect is empty the other
+    /// is returned unchanged, and if both are empty the result is `NSRect::zero()`.
+    pub fn union(&self, other: &NSRect) -> NSRect {
+        match (self.is_empty(), other.is_empty()) {
+            (true, true) => NSRect::zero(),
+            (true, false) => *other,
+            (false, true) => *self,
+            (false, false) => {
+                let min_x = self.min_x().min(other.min_x());
+                let min_y = self.min_y().min(other.min_y());
+                let max_x = self.max_x().max(other.max_x());
+                let max_y = self.max_y().max(other.max_y());
+                NSRect::from_coords(min_x, min_y, max_x - min_x, max_y - min_y)
+            }
+        }
+    }
+
+    /// Returns the rect moved by `dx` and `dy`, like `NSOffsetRect`.
+    #[inline]
+    pub fn offset(&self, dx: CGFloat, dy: CGFloat) -> NSRect {
+        NSRect::from_coords(self.origin.x + dx, self.origin.y + dy,
+                            self.size.width, self.size.height)
+    }
+
+    /// Returns the rect shrunk by `dx` on the left and right and by `dy` on the top and
+    /// bottom, like `NSInsetRect`. Negative values grow the rect instead.
+    #[inline]
+    pub fn inset(&self, dx: CGFloat, dy: CGFloat) -> NSRect {
+        NSRect::from_coords(self.origin.x + dx, self.origin.y + dy,
+                            self.size.width - 2. * dx, self.size.height - 2. * dy)
+    }
+
+    /// Returns the smallest rect with integral coordinates that encloses this one, like
+    /// `NSIntegralRect`.
+    ///
+    /// An empty rect yields `NSRect::zero()`.
+    pub fn integral(&self) -> NSRect {
+        if self.is_empty() {
+            return NSRect::zero();
+        }
+        let min_x = self.min_x().floor();
+        let min_y = self.min_y().floor();
+        let max_x = self.max_x().ceil();
+        let max_y = self.max_y().ceil();
+        NSRect::from_coords(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    /// Splits the rect in two along `edge`, like `NSDivideRect`.
+    ///
+    /// Returns `(slice, remainder)`, where `slice` is the part within `amount` of `edge`.
+    /// `amount` is clamped to the size of the rect, so the remainder may have zero width
+    /// or height. An empty rect divides into two zero rects.
+    pub fn divide(&self, amount: CGFloat, edge: NSRectEdge) -> (NSRect, NSRect) {
+        if self.is_empty() {
+            return (NSRect::zero(), NSRect::zero());
+        }
+        let (x, y, width, height) = (self.origin.x, self.origin.y,
+                                     self.size.width, self.size.height);
+        match edge {
+            NSRectEdge::NSRectMinXEdge => {
+                let amount = amount.max(0.).min(width);
+                (NSRect::from_coords(x, y, amount, height),
+                 NSRect::from_coords(x + amount, y, width - amount, height))
+            }
+            NSRectEdge::NSRectMinYEdge => {
+                let amount = amount.max(0.).min(height);
+                (NSRect::from_coords(x, y, width, amount),
+                 NSRect::from_coords(x, y + amount, width, height - amount))
+            }
+            NSRectEdge::NSRectMaxXEdge => {
+                let amount = amount.max(0.).min(width);
+                (NSRect::from_coords(x + width - amount, y, amount, height),
+                 NSRect::from_coords(x, y, width - amount, height))
+            }
+            NSRectEdge::NSRectMaxYEdge => {
+                let amount = amount.max(0.).min(height);
+                (NSRect::from_coords(x, y + height - amount, width, amount),
+                 NSRect::from_coords(x, y, width, height - amount))
+            }
+        }
+    }
 }
 
 #[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NSRange {
     pub location: NSUInteger,
     pub length: NSUInteger,
@@ -103,156 +366,2576 @@ impl NSRange {
             length: length
         }
     }
+
+    /// The location just past the end of the range, like `NSMaxRange`.
+    ///
+    /// Ranges which extend past `NSUInteger::MAX` are clamped to it rather than
+    /// overflowing.
+    #[inline]
+    pub fn max(&self) -> NSUInteger {
+        self.location.saturating_add(self.length)
+    }
+
+    /// Returns true if `location` falls within the range, like `NSLocationInRange`.
+    #[inline]
+    pub fn contains(&self, location: NSUInteger) -> bool {
+        location >= self.location && location - self.location < self.length
+    }
+
+    /// Returns the range covered by both ranges, like `NSIntersectionRange`.
+    ///
+    /// Ranges which do not overlap produce `{0, 0}`. Ranges which only touch produce an
+    /// empty range at the point where they meet.
+    pub fn intersection(&self, other: &NSRange) -> NSRange {
+        if self.max() < other.location || other.max() < self.location {
+            return NSRange::new(0, 0);
+        }
+        let location = if self.location > other.location { self.location } else { other.location };
+        let max = if self.max() < other.max() { self.max() } else { other.max() };
+        NSRange::new(location, max - location)
+    }
+
+    /// Returns the smallest range covering both ranges, like `NSUnionRange`.
+    pub fn union(&self, other: &NSRange) -> NSRange {
+        let location = if self.location < other.location { self.location } else { other.location };
+        let max = if self.max() > other.max() { self.max() } else { other.max() };
+        NSRange::new(location, max - location)
+    }
+
+    /// Converts a byte range over `string` into the range of UTF-16 code units that
+    /// `NSString` uses for the same characters.
+    ///
+    /// Returns `None`, where slicing `string` would panic, if `range` is reversed or if
+    /// either end of it is past the end of `string` or inside a multi-byte character;
+    /// offsets are never rounded to the nearest character boundary.
+    pub fn from_utf8_range(string: &str, range: Range<usize>) -> Option<NSRange> {
+        if range.start > range.end ||
+           !string.is_char_boundary(range.start) || !string.is_char_boundary(range.end) {
+            return None;
+        }
+        let location = string[..range.start].encode_utf16().count();
+        let length = string[range.start..range.end].encode_utf16().count();
+        Some(NSRange::new(location as NSUInteger, length as NSUInteger))
+    }
+
+    /// Converts a range of UTF-16 code units, as used by `NSString`, into the byte range
+    /// of the same characters in `string`.
+    ///
+    /// The returned range always starts and ends on character boundaries of `string`.
+    /// Returns `None` if the range extends past the end of `string` or if either end
+    /// falls between the two halves of a surrogate pair, which has no UTF-8 offset.
+    pub fn to_utf8_range(&self, string: &str) -> Option<Range<usize>> {
+        let start = utf16_to_utf8_offset(string, self.location as usize)?;
+        let len = utf16_to_utf8_offset(&string[start..], self.length as usize)?;
+        Some(start..start + len)
+    }
+}
+
+unsafe impl objc::Encode for NSRange {
+    fn encode() -> objc::Encoding {
+        let encoding = format!("{{_NSRange={}{}}}",
+                               NSUInteger::encode().as_str(),
+                               NSUInteger::encode().as_str());
+        unsafe { objc::Encoding::from_str(&encoding) }
+    }
+}
+
+fn utf16_to_utf8_offset(string: &str, offset: usize) -> Option<usize> {
+    let mut units = 0;
+    for (index, c) in string.char_indices() {
+        if units == offset {
+            return Some(index);
+        }
+        units += c.len_utf16();
+        if units > offset {
+            return None;
+        }
+    }
+    if units == offset { Some(string.len()) } else { None }
+}
+
+/// A range whose start is past its end is empty, so it converts to an empty range at
+/// its start.
+impl From<Range<usize>> for NSRange {
+    #[inline]
+    fn from(range: Range<usize>) -> NSRange {
+        NSRange::new(range.start as NSUInteger, range.end.saturating_sub(range.start) as NSUInteger)
+    }
+}
+
+impl From<NSRange> for Range<usize> {
+    #[inline]
+    fn from(range: NSRange) -> Range<usize> {
+        (range.location as usize)..(range.max() as usize)
+    }
 }
 
 unsafe impl objc::Encode for NSRect {
     fn encode() -> objc::Encoding {
-        let encoding = format!("{{CGRect={}{}}}",
+        let encoding = format!("{{{}={}{}}}",
+                               NSRECT_STRUCT_NAME,
                                NSPoint::encode().as_str(),
                                NSSize::encode().as_str());
         unsafe { objc::Encoding::from_str(&encoding) }
     }
 }
 
-// Same as CGRectEdge
-#[repr(u32)]
-pub enum NSRectEdge {
-    NSRectMinXEdge,
-    NSRectMinYEdge,
-    NSRectMaxXEdge,
-    NSRectMaxYEdge,
+// NSPoint, NSSize and NSRect have the same layout as the Core Graphics types.
+
+#[cfg(target_os = "macos")]
+impl From<CGPoint> for NSPoint {
+    #[inline]
+    fn from(point: CGPoint) -> NSPoint {
+        NSPoint::new(point.x, point.y)
+    }
 }
 
-#[link(name = "Foundation", kind = "framework")]
-extern {
-    pub static NSDefaultRunLoopMode: id;
+#[cfg(target_os = "macos")]
+impl From<NSPoint> for CGPoint {
+    #[inline]
+    fn from(point: NSPoint) -> CGPoint {
+        CGPoint {
+            x: point.x,
+            y: point.y,
+        }
+    }
 }
 
-pub trait NSAutoreleasePool {
-    unsafe fn new(_: Self) -> id {
-        msg_send![class("NSAutoreleasePool"), new]
+#[cfg(target_os = "macos")]
+impl From<CGSize> for NSSize {
+    #[inline]
+    fn from(size: CGSize) -> NSSize {
+        NSSize::new(size.width, size.height)
     }
+}
 
-    unsafe fn autorelease(self) -> Self;
-    unsafe fn drain(self);
+#[cfg(target_os = "macos")]
+impl From<NSSize> for CGSize {
+    #[inline]
+    fn from(size: NSSize) -> CGSize {
+        CGSize {
+            width: size.width,
+            height: size.height,
+        }
+    }
 }
 
-impl NSAutoreleasePool for id {
-    unsafe fn autorelease(self) -> id {
-        msg_send![self, autorelease]
+#[cfg(target_os = "macos")]
+impl From<CGRect> for NSRect {
+    #[inline]
+    fn from(rect: CGRect) -> NSRect {
+        NSRect::new(rect.origin.into(), rect.size.into())
     }
+}
 
-    unsafe fn drain(self) {
-        msg_send![self, drain]
+#[cfg(target_os = "macos")]
+impl From<NSRect> for CGRect {
+    #[inline]
+    fn from(rect: NSRect) -> CGRect {
+        CGRect {
+            origin: rect.origin.into(),
+            size: rect.size.into(),
+        }
     }
 }
 
-pub trait NSProcessInfo {
-    unsafe fn processInfo(_: Self) -> id {
-        msg_send![class("NSProcessInfo"), processInfo]
+#[cfg(feature = "euclid")]
+mod euclid_conversions {
+    use euclid::{Point2D, Rect, Size2D};
+    use super::{CGFloat, NSPoint, NSRect, NSSize};
+
+    impl<U> From<Point2D<CGFloat, U>> for NSPoint {
+        #[inline]
+        fn from(point: Point2D<CGFloat, U>) -> NSPoint {
+            NSPoint::new(point.x, point.y)
+        }
     }
 
-    unsafe fn processName(self) -> id;
-}
+    impl<U> From<NSPoint> for Point2D<CGFloat, U> {
+        #[inline]
+        fn from(point: NSPoint) -> Point2D<CGFloat, U> {
+            Point2D::new(point.x, point.y)
+        }
+    }
 
-impl NSProcessInfo for id {
-    unsafe fn processName(self) -> id {
-        msg_send![self, processName]
+    impl<U> From<Size2D<CGFloat, U>> for NSSize {
+        #[inline]
+        fn from(size: Size2D<CGFloat, U>) -> NSSize {
+            NSSize::new(size.width, size.height)
+        }
+    }
+
+    impl<U> From<NSSize> for Size2D<CGFloat, U> {
+        #[inline]
+        fn from(size: NSSize) -> Size2D<CGFloat, U> {
+            Size2D::new(size.width, size.height)
+        }
+    }
+
+    impl<U> From<Rect<CGFloat, U>> for NSRect {
+        #[inline]
+        fn from(rect: Rect<CGFloat, U>) -> NSRect {
+            NSRect::new(rect.origin.into(), rect.size.into())
+        }
+    }
+
+    impl<U> From<NSRect> for Rect<CGFloat, U> {
+        #[inline]
+        fn from(rect: NSRect) -> Rect<CGFloat, U> {
+            Rect::new(rect.origin.into(), rect.size.into())
+        }
     }
 }
 
-pub type NSTimeInterval = libc::c_double;
+// String representations compatible with NSStringFromPoint, NSStringFromRect,
+// NSStringFromRange and friends. Parsing follows NSPointFromString and friends: the
+// string is scanned for numbers, anything between them is ignored, and any component
+// which is missing is zero. Like those functions, parsing never fails.
 
-pub trait NSValue {
-    unsafe fn valueWithPoint(_: Self, point: NSPoint) -> id {
-        msg_send![class("NSValue"), valueWithPoint:point]
+impl fmt::Display for NSPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{}, {}}}", FormatG(self.x), FormatG(self.y))
     }
+}
 
-    unsafe fn valueWithSize(_: Self, size: NSSize) -> id {
-        msg_send![class("NSValue"), valueWithSize:size]
+impl fmt::Display for NSSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{}, {}}}", FormatG(self.width), FormatG(self.height))
     }
 }
 
-impl NSValue for id {
+impl fmt::Display for NSRect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{}, {}}}", self.origin, self.size)
+    }
 }
 
-pub trait NSArray {
-    unsafe fn array(_: Self) -> id {
-        msg_send![class("NSArray"), array]
+// Writes a CGFloat the way NSStringFromPoint and friends do: with printf's `%.17g` where
+// CGFloat is a double, and `%g` where it's a float. Like Apple's printf, NaN is written as
+// `nan` whatever its sign.
+struct FormatG(CGFloat);
+
+impl fmt::Display for FormatG {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        #[cfg(target_pointer_width = "32")]
+        const PRECISION: usize = 6;
+        #[cfg(target_pointer_width = "64")]
+        const PRECISION: usize = 17;
+
+        let value = f64::from(self.0);
+        if value.is_nan() {
+            return f.write_str("nan");
+        }
+        if value.is_infinite() {
+            return f.write_str(if value < 0. { "-inf" } else { "inf" });
+        }
+        if value == 0. {
+            return f.write_str(if value.is_sign_negative() { "-0" } else { "0" });
+        }
+
+        // `%g` uses the exponent `%e` would, after rounding to the precision, to choose
+        // between fixed and scientific notation, and then drops trailing zeros.
+        fn trim_zeros(digits: &str) -> &str {
+            if digits.contains('.') {
+                digits.trim_end_matches('0').trim_end_matches('.')
+            } else {
+                digits
+            }
+        }
+
+        let scientific = format!("{:.*e}", PRECISION - 1, value);
+        let e = scientific.find('e').unwrap();
+        let exponent: i32 = scientific[e + 1..].parse().unwrap();
+        if exponent < -4 || exponent >= PRECISION as i32 {
+            write!(f, "{}e{}{:02}", trim_zeros(&scientific[..e]),
+                   if exponent < 0 { '-' } else { '+' }, exponent.abs())
+        } else {
+            let fixed = format!("{:.*}", (PRECISION as i32 - 1 - exponent) as usize, value);
+            f.write_str(trim_zeros(&fixed))
+        }
     }
+}
 
-    unsafe fn arrayWithObjects(_: Self, objects: &[id]) -> id {
-        msg_send![class("NSArray"), arrayWithObjects:objects.as_ptr()
-                                    count:objects.len()]
+impl fmt::Display for NSRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{}, {}}}", self.location, self.length)
     }
+}
+
+/// Parses the `{x, y}` format written by `Display` as leniently as `NSPointFromString`:
+/// the first two decimal numbers in the string are used, whatever surrounds them, and
+/// any that are missing are 0. Parsing never fails.
+impl FromStr for NSPoint {
+    type Err = ParseError;
 
-    unsafe fn arrayWithObject(_: Self, object: id) -> id {
-        msg_send![class("NSArray"), arrayWithObject:object]
+    fn from_str(s: &str) -> Result<NSPoint, ParseError> {
+        let mut scanner = NumberScanner::new(s);
+        let x = scanner.next_float() as CGFloat;
+        let y = scanner.next_float() as CGFloat;
+        Ok(NSPoint::new(x, y))
     }
+}
 
-    unsafe fn arrayByAddingObjectFromArray(self, object: id) -> id;
-    unsafe fn arrayByAddingObjectsFromArray(self, objects: id) -> id;
+/// Parses `{width, height}` with the same leniency as `NSPoint`.
+impl FromStr for NSSize {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<NSSize, ParseError> {
+        let mut scanner = NumberScanner::new(s);
+        let width = scanner.next_float() as CGFloat;
+        let height = scanner.next_float() as CGFloat;
+        Ok(NSSize::new(width, height))
+    }
 }
 
-impl NSArray for id {
-    unsafe fn arrayByAddingObjectFromArray(self, object: id) -> id {
-        msg_send![self, arrayByAddingObjectFromArray:object]
+/// Parses `{{x, y}, {width, height}}` with the same leniency as `NSPoint`, from the
+/// first four decimal numbers in the string.
+impl FromStr for NSRect {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<NSRect, ParseError> {
+        let mut scanner = NumberScanner::new(s);
+        let x = scanner.next_float() as CGFloat;
+        let y = scanner.next_float() as CGFloat;
+        let width = scanner.next_float() as CGFloat;
+        let height = scanner.next_float() as CGFloat;
+        Ok(NSRect::new(NSPoint::new(x, y), NSSize::new(width, height)))
     }
+}
 
-    unsafe fn arrayByAddingObjectsFromArray(self, objects: id) -> id {
-        msg_send![self, arrayByAddingObjectsFromArray:objects]
+/// Parses the `{location, length}` format written by `Display` as leniently as
+/// `NSRangeFromString`: the first two runs of digits in the string are used, and any
+/// that are missing are 0. Everything before a digit is skipped, including a minus
+/// sign, so `{-1, 2}` parses as `{1, 2}`, and values too large for an `NSUInteger`
+/// saturate. Parsing never fails.
+impl FromStr for NSRange {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<NSRange, ParseError> {
+        let mut scanner = NumberScanner::new(s);
+        let location = scanner.next_integer();
+        let length = scanner.next_integer();
+        Ok(NSRange::new(location, length))
     }
 }
 
-pub trait NSString {
-    unsafe fn alloc(_: Self) -> id {
-        msg_send![class("NSString"), alloc]
+struct NumberScanner<'a> {
+    rest: &'a str,
+}
+
+impl<'a> NumberScanner<'a> {
+    fn new(s: &'a str) -> NumberScanner<'a> {
+        NumberScanner {
+            rest: s,
+        }
     }
 
-    unsafe fn stringByAppendingString_(self, other: id) -> id;
-    unsafe fn init_str(self, string: &str) -> Self;
-    unsafe fn UTF8String(self) -> *const libc::c_char;
-    unsafe fn len(self) -> usize;
-    unsafe fn isEqualToString(self, &str) -> bool;
+    /// Skips to the next decimal number and returns its value, or 0 if there is none.
+    fn next_float(&mut self) -> f64 {
+        let bytes = self.rest.as_bytes();
+        for start in 0..bytes.len() {
+            let len = float_prefix_len(&bytes[start..]);
+            if len > 0 {
+                let number = &self.rest[start..start + len];
+                self.rest = &self.rest[start + len..];
+                return number.parse().unwrap_or(0.);
+            }
+        }
+        self.rest = "";
+        0.
+    }
+
+    /// Skips to the next run of digits and returns its value, or 0 if there is none.
+    /// Values too large for an `NSUInteger` saturate.
+    fn next_integer(&mut self) -> NSUInteger {
+        let start = match self.rest.bytes().position(|b| b.is_ascii_digit()) {
+            Some(start) => start,
+            None => {
+                self.rest = "";
+                return 0;
+            }
+        };
+        let mut value: NSUInteger = 0;
+        let mut end = start;
+        for b in self.rest[start..].bytes().take_while(|b| b.is_ascii_digit()) {
+            value = value.saturating_mul(10).saturating_add((b - b'0') as NSUInteger);
+            end += 1;
+        }
+        self.rest = &self.rest[end..];
+        value
+    }
 }
 
-impl NSString for id {
-    unsafe fn isEqualToString(self, other: &str) -> bool {
-        let other = NSString::alloc(nil).init_str(other);
-        let rv: BOOL = msg_send![self, isEqualToString:other];
-        rv != 0
+/// Returns the length of the number at the start of `bytes`, in the syntax accepted by
+/// `strtod` minus hexadecimal and special values, or 0 if there is none.
+fn float_prefix_len(bytes: &[u8]) -> usize {
+    let digits_from = |from: usize| bytes[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+
+    let mut len = 0;
+    if len < bytes.len() && (bytes[len] == b'+' || bytes[len] == b'-') {
+        len += 1;
+    }
+    let integer_digits = digits_from(len);
+    len += integer_digits;
+    let mut fraction_digits = 0;
+    if len < bytes.len() && bytes[len] == b'.' {
+        fraction_digits = digits_from(len + 1);
+        if integer_digits > 0 || fraction_digits > 0 {
+            len += 1 + fraction_digits;
+        }
+    }
+    if integer_digits == 0 && fraction_digits == 0 {
+        return 0;
     }
+    if len < bytes.len() && (bytes[len] == b'e' || bytes[len] == b'E') {
+        let mut exponent = len + 1;
+        if exponent < bytes.len() && (bytes[exponent] == b'+' || bytes[exponent] == b'-') {
+            exponent += 1;
+        }
+        let exponent_digits = digits_from(exponent);
+        if exponent_digits > 0 {
+            len = exponent + exponent_digits;
+        }
+    }
+    len
+}
 
-    unsafe fn stringByAppendingString_(self, other: id) -> id {
-        msg_send![self, stringByAppendingString:other]
+// Same values as CGRectEdge, but an NSUInteger like the other Foundation enums
+#[cfg_attr(target_pointer_width = "32", repr(u32))]
+#[cfg_attr(target_pointer_width = "64", repr(u64))]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NSRectEdge {
+    NSRectMinXEdge,
+    NSRectMinYEdge,
+    NSRectMaxXEdge,
+    NSRectMaxYEdge,
+}
+
+encode_as!(NSRectEdge => NSUInteger);
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NSEdgeInsets {
+    pub top: CGFloat,
+    pub left: CGFloat,
+    pub bottom: CGFloat,
+    pub right: CGFloat,
+}
+
+impl NSEdgeInsets {
+    #[inline]
+    pub fn new(top: CGFloat, left: CGFloat, bottom: CGFloat, right: CGFloat) -> NSEdgeInsets {
+        NSEdgeInsets {
+            top: top,
+            left: left,
+            bottom: bottom,
+            right: right,
+        }
     }
 
-    unsafe fn init_str(self, string: &str) -> id {
-        return msg_send![self,
-                         initWithBytes:string.as_ptr()
-                             length:string.len()
-                             encoding:UTF8_ENCODING as id];
+    /// Insets of zero on every side, like `NSEdgeInsetsZero`.
+    #[inline]
+    pub fn zero() -> NSEdgeInsets {
+        NSEdgeInsets::new(0., 0., 0., 0.)
     }
 
-    unsafe fn len(self) -> usize {
-        msg_send![self, lengthOfBytesUsingEncoding:UTF8_ENCODING]
+    /// Returns the part of `rect` inside the insets.
+    ///
+    /// The top inset is applied at the maximum y edge unless `flipped` is set, in which
+    /// case it is applied at the minimum y edge.
+    pub fn inset_rect(&self, rect: NSRect, flipped: bool) -> NSRect {
+        let y_inset = if flipped { self.top } else { self.bottom };
+        NSRect::new(NSPoint::new(rect.origin.x + self.left, rect.origin.y + y_inset),
+                    NSSize::new(rect.size.width - self.left - self.right,
+                                rect.size.height - self.top - self.bottom))
     }
+}
 
-    unsafe fn UTF8String(self) -> *const libc::c_char {
-        msg_send![self, UTF8String]
+unsafe impl objc::Encode for NSEdgeInsets {
+    fn encode() -> objc::Encoding {
+        let encoding = format!("{{NSEdgeInsets={}{}{}{}}}",
+                               CGFloat::encode().as_str(),
+                               CGFloat::encode().as_str(),
+                               CGFloat::encode().as_str(),
+                               CGFloat::encode().as_str());
+        unsafe { objc::Encoding::from_str(&encoding) }
     }
 }
 
-pub trait NSDate {
-    unsafe fn distantPast(_: Self) -> id {
-        msg_send![class("NSDate"), distantPast]
+/// The matrix of an `NSAffineTransform`.
+///
+/// Points are treated as row vectors, so a point (x, y) maps to
+/// (m11 x + m21 y + tX, m12 x + m22 y + tY).
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NSAffineTransformStruct {
+    pub m11: CGFloat,
+    pub m12: CGFloat,
+    pub m21: CGFloat,
+    pub m22: CGFloat,
+    pub tX: CGFloat,
+    pub tY: CGFloat,
+}
+
+impl NSAffineTransformStruct {
+    #[inline]
+    pub fn new(m11: CGFloat, m12: CGFloat,
+               m21: CGFloat, m22: CGFloat,
+               tX: CGFloat, tY: CGFloat) -> NSAffineTransformStruct {
+        NSAffineTransformStruct {
+            m11: m11,
+            m12: m12,
+            m21: m21,
+            m22: m22,
+            tX: tX,
+            tY: tY,
+        }
     }
 
-    unsafe fn distantFuture(_: Self) -> id {
-        msg_send![class("NSDate"), distantFuture]
+    #[inline]
+    pub fn identity() -> NSAffineTransformStruct {
+        NSAffineTransformStruct::new(1., 0., 0., 1., 0., 0.)
+    }
+
+    #[inline]
+    pub fn translation(tx: CGFloat, ty: CGFloat) -> NSAffineTransformStruct {
+        NSAffineTransformStruct::new(1., 0., 0., 1., tx, ty)
+    }
+
+    #[inline]
+    pub fn scale(sx: CGFloat, sy: CGFloat) -> NSAffineTransformStruct {
+        NSAffineTransformStruct::new(sx, 0., 0., sy, 0., 0.)
+    }
+
+    /// A rotation by `angle` radians, counterclockwise in an unflipped coordinate system.
+    #[inline]
+    pub fn rotation(angle: CGFloat) -> NSAffineTransformStruct {
+        let (sin, cos) = angle.sin_cos();
+        NSAffineTransformStruct::new(cos, sin, -sin, cos, 0., 0.)
+    }
+
+    /// A rotation by `angle` degrees, counterclockwise in an unflipped coordinate system.
+    #[inline]
+    pub fn rotation_degrees(angle: CGFloat) -> NSAffineTransformStruct {
+        NSAffineTransformStruct::rotation(angle.to_radians())
+    }
+
+    #[inline]
+    pub fn is_identity(&self) -> bool {
+        *self == NSAffineTransformStruct::identity()
     }
-}
 
-impl NSDate for id {
+    /// Returns the transform which applies `self` and then `other`, like
+    /// `appendTransform:`.
+    pub fn append(&self, other: &NSAffineTransformStruct) -> NSAffineTransformStruct {
+        NSAffineTransformStruct::new(
+            self.m11 * other.m11 + self.m12 * other.m21,
+            self.m11 * other.m12 + self.m12 * other.m22,
+            self.m21 * other.m11 + self.m22 * other.m21,
+            self.m21 * other.m12 + self.m22 * other.m22,
+            self.tX * other.m11 + self.tY * other.m21 + other.tX,
+            self.tX * other.m12 + self.tY * other.m22 + other.tY)
+    }
 
+    /// Returns the transform which applies `other` and then `self`, like
+    /// `prependTransform:`.
+    #[inline]
+    pub fn prepend(&self, other: &NSAffineTransformStruct) -> NSAffineTransformStruct {
+        other.append(self)
+    }
+
+    /// Prepends a translation, like `translateXBy:yBy:`.
+    #[inline]
+    pub fn translate_by(&self, tx: CGFloat, ty: CGFloat) -> NSAffineTransformStruct {
+        self.prepend(&NSAffineTransformStruct::translation(tx, ty))
+    }
+
+    /// Prepends a scale, like `scaleXBy:yBy:`.
+    #[inline]
+    pub fn scale_by(&self, sx: CGFloat, sy: CGFloat) -> NSAffineTransformStruct {
+        self.prepend(&NSAffineTransformStruct::scale(sx, sy))
+    }
+
+    /// Prepends a rotation by `angle` radians, like `rotateByRadians:`.
+    #[inline]
+    pub fn rotate_by(&self, angle: CGFloat) -> NSAffineTransformStruct {
+        self.prepend(&NSAffineTransformStruct::rotation(angle))
+    }
+
+    /// Prepends a rotation by `angle` degrees, like `rotateByDegrees:`.
+    #[inline]
+    pub fn rotate_by_degrees(&self, angle: CGFloat) -> NSAffineTransformStruct {
+        self.prepend(&NSAffineTransformStruct::rotation_degrees(angle))
+    }
+
+    /// Returns the inverse transform, like `invert`, or `None` if the matrix is singular.
+    pub fn invert(&self) -> Option<NSAffineTransformStruct> {
+        let determinant = self.m11 * self.m22 - self.m12 * self.m21;
+        if determinant == 0. || !determinant.is_finite() {
+            return None;
+        }
+        Some(NSAffineTransformStruct::new(
+            self.m22 / determinant,
+            -self.m12 / determinant,
+            -self.m21 / determinant,
+            self.m11 / determinant,
+            (self.m21 * self.tY - self.m22 * self.tX) / determinant,
+            (self.m12 * self.tX - self.m11 * self.tY) / determinant))
+    }
+
+    /// Applies the transform to a point, like `transformPoint:`.
+    #[inline]
+    pub fn transform_point(&self, point: NSPoint) -> NSPoint {
+        NSPoint::new(self.m11 * point.x + self.m21 * point.y + self.tX,
+                     self.m12 * point.x + self.m22 * point.y + self.tY)
+    }
+
+    /// Applies the transform to a size, like `transformSize:`. The translation is
+    /// ignored, and a rotation or flip may produce negative components.
+    #[inline]
+    pub fn transform_size(&self, size: NSSize) -> NSSize {
+        NSSize::new(self.m11 * size.width + self.m21 * size.height,
+                    self.m12 * size.width + self.m22 * size.height)
+    }
+
+    /// Returns the smallest rect enclosing the transformed corners of `rect`.
+    pub fn transform_rect(&self, rect: NSRect) -> NSRect {
+        let corners = [
+            self.transform_point(NSPoint::new(rect.min_x(), rect.min_y())),
+            self.transform_point(NSPoint::new(rect.max_x(), rect.min_y())),
+            self.transform_point(NSPoint::new(rect.min_x(), rect.max_y())),
+            self.transform_point(NSPoint::new(rect.max_x(), rect.max_y())),
+        ];
+        let (mut min_x, mut min_y) = (corners[0].x, corners[0].y);
+        let (mut max_x, mut max_y) = (min_x, min_y);
+        for corner in &corners[1..] {
+            min_x = min_x.min(corner.x);
+            min_y = min_y.min(corner.y);
+            max_x = max_x.max(corner.x);
+            max_y = max_y.max(corner.y);
+        }
+        NSRect::new(NSPoint::new(min_x, min_y), NSSize::new(max_x - min_x, max_y - min_y))
+    }
+}
+
+unsafe impl objc::Encode for NSAffineTransformStruct {
+    fn encode() -> objc::Encoding {
+        let encoding = format!("{{_NSAffineTransformStruct={}{}{}{}{}{}}}",
+                               CGFloat::encode().as_str(),
+                               CGFloat::encode().as_str(),
+                               CGFloat::encode().as_str(),
+                               CGFloat::encode().as_str(),
+                               CGFloat::encode().as_str(),
+                               CGFloat::encode().as_str());
+        unsafe { objc::Encoding::from_str(&encoding) }
+    }
+}
+
+#[cfg_attr(target_os = "macos", link(name = "Foundation", kind = "framework"))]
+extern {
+    pub static NSDefaultRunLoopMode: id;
+}
+
+object_ref! {
+    /// The root class, which every other handle is a kind of.
+    pub struct NSObjectRef(NSObject);
+}
+
+object_ref! {
+    pub struct NSAutoreleasePoolRef(NSAutoreleasePool): NSObjectRef;
+}
+
+/// A manually drained autorelease pool. `rc::autoreleasepool` is easier to use correctly,
+/// since it drains the pool on every exit path.
+pub trait NSAutoreleasePool {
+    unsafe fn new(_: Self) -> id {
+        msg_send![class!(NSAutoreleasePool), new]
+    }
+
+    unsafe fn autorelease(self) -> Self;
+    unsafe fn drain(self);
+}
+
+impl<T: IsKindOf<NSAutoreleasePoolRef>> NSAutoreleasePool for T {
+    unsafe fn autorelease(self) -> T {
+        T::from_id(msg_send![self.as_id(), autorelease])
+    }
+
+    unsafe fn drain(self) {
+        msg_send![self.as_id(), drain]
+    }
+}
+
+object_ref! {
+    pub struct NSProcessInfoRef(NSProcessInfo): NSObjectRef;
+}
+
+pub trait NSProcessInfo {
+    unsafe fn processInfo(_: Self) -> id {
+        msg_send![class!(NSProcessInfo), processInfo]
+    }
+
+    unsafe fn processName(self) -> id;
+}
+
+impl<T: IsKindOf<NSProcessInfoRef>> NSProcessInfo for T {
+    unsafe fn processName(self) -> id {
+        msg_send![self.as_id(), processName]
+    }
+}
+
+pub type NSTimeInterval = libc::c_double;
+
+object_ref! {
+    pub struct NSValueRef(NSValue): NSObjectRef;
+}
+
+pub trait NSValue {
+    unsafe fn valueWithPoint(_: Self, point: NSPoint) -> id {
+        msg_send![class!(NSValue), valueWithPoint:point]
+    }
+
+    unsafe fn valueWithSize(_: Self, size: NSSize) -> id {
+        msg_send![class!(NSValue), valueWithSize:size]
+    }
+
+    unsafe fn valueWithRect(_: Self, rect: NSRect) -> id {
+        msg_send![class!(NSValue), valueWithRect:rect]
+    }
+
+    unsafe fn valueWithRange(_: Self, range: NSRange) -> id {
+        msg_send![class!(NSValue), valueWithRange:range]
+    }
+
+    unsafe fn pointValue(self) -> NSPoint;
+    unsafe fn sizeValue(self) -> NSSize;
+    unsafe fn rectValue(self) -> NSRect;
+    unsafe fn rangeValue(self) -> NSRange;
+
+    /// The type encoding of the value, such as `{CGRect={CGPoint=dd}{CGSize=dd}}`.
+    unsafe fn objCType(self) -> *const libc::c_char;
+    unsafe fn isEqualToValue_(self, value: id) -> BOOL;
+}
+
+impl<T: IsKindOf<NSValueRef>> NSValue for T {
+    unsafe fn pointValue(self) -> NSPoint {
+        msg_send![self.as_id(), pointValue]
+    }
+
+    unsafe fn sizeValue(self) -> NSSize {
+        msg_send![self.as_id(), sizeValue]
+    }
+
+    unsafe fn rectValue(self) -> NSRect {
+        msg_send![self.as_id(), rectValue]
+    }
+
+    unsafe fn rangeValue(self) -> NSRange {
+        msg_send![self.as_id(), rangeValue]
+    }
+
+    unsafe fn objCType(self) -> *const libc::c_char {
+        msg_send![self.as_id(), objCType]
+    }
+
+    unsafe fn isEqualToValue_(self, value: id) -> BOOL {
+        msg_send![self.as_id(), isEqualToValue:value]
+    }
+}
+
+object_ref! {
+    pub struct NSNumberRef(NSNumber): NSValueRef, NSObjectRef;
+}
+
+pub trait NSNumber {
+    unsafe fn alloc(_: Self) -> id {
+        msg_send![class!(NSNumber), alloc]
+    }
+
+    unsafe fn numberWithBool(_: Self, value: BOOL) -> id {
+        msg_send![class!(NSNumber), numberWithBool:value]
+    }
+
+    unsafe fn numberWithLongLong(_: Self, value: libc::c_longlong) -> id {
+        msg_send![class!(NSNumber), numberWithLongLong:value]
+    }
+
+    unsafe fn numberWithUnsignedLongLong(_: Self, value: libc::c_ulonglong) -> id {
+        msg_send![class!(NSNumber), numberWithUnsignedLongLong:value]
+    }
+
+    unsafe fn numberWithInteger(_: Self, value: NSInteger) -> id {
+        msg_send![class!(NSNumber), numberWithInteger:value]
+    }
+
+    unsafe fn numberWithUnsignedInteger(_: Self, value: NSUInteger) -> id {
+        msg_send![class!(NSNumber), numberWithUnsignedInteger:value]
+    }
+
+    unsafe fn numberWithDouble(_: Self, value: libc::c_double) -> id {
+        msg_send![class!(NSNumber), numberWithDouble:value]
+    }
+
+    // Accessing Numeric Values
+    unsafe fn boolValue(self) -> BOOL;
+    unsafe fn longLongValue(self) -> libc::c_longlong;
+    unsafe fn unsignedLongLongValue(self) -> libc::c_ulonglong;
+    unsafe fn integerValue(self) -> NSInteger;
+    unsafe fn unsignedIntegerValue(self) -> NSUInteger;
+    unsafe fn doubleValue(self) -> libc::c_double;
+    unsafe fn stringValue(self) -> id;
+
+    /// The value, read as the kind of scalar `objCType` says the number holds.
+    unsafe fn value(self) -> NSNumberValue;
+    /// The value as `S`, or `None` if it can't be represented as one.
+    unsafe fn get<S: NSNumberScalar>(self) -> Option<S>;
+
+    // Comparing Numbers
+    unsafe fn compare_(self, other: id) -> NSComparisonResult;
+    unsafe fn isEqualToNumber_(self, other: id) -> BOOL;
+}
+
+impl<T: IsKindOf<NSNumberRef>> NSNumber for T {
+    // Accessing Numeric Values
+
+    unsafe fn boolValue(self) -> BOOL {
+        msg_send![self.as_id(), boolValue]
+    }
+
+    unsafe fn longLongValue(self) -> libc::c_longlong {
+        msg_send![self.as_id(), longLongValue]
+    }
+
+    unsafe fn unsignedLongLongValue(self) -> libc::c_ulonglong {
+        msg_send![self.as_id(), unsignedLongLongValue]
+    }
+
+    unsafe fn integerValue(self) -> NSInteger {
+        msg_send![self.as_id(), integerValue]
+    }
+
+    unsafe fn unsignedIntegerValue(self) -> NSUInteger {
+        msg_send![self.as_id(), unsignedIntegerValue]
+    }
+
+    unsafe fn doubleValue(self) -> libc::c_double {
+        msg_send![self.as_id(), doubleValue]
+    }
+
+    unsafe fn stringValue(self) -> id {
+        msg_send![self.as_id(), stringValue]
+    }
+
+    unsafe fn value(self) -> NSNumberValue {
+        let obj = self.as_id();
+        let encoding = CStr::from_ptr(NSValueRef::from_id(obj).objCType()).to_bytes();
+        match encoding.first() {
+            // Booleans are the two `kCFBoolean` singletons, which are encoded as `char`.
+            Some(&b'B') => NSNumberValue::Bool(self.boolValue() != NO),
+            Some(&b'c') if obj == NSNumber::numberWithBool(nil, YES) => NSNumberValue::Bool(true),
+            Some(&b'c') if obj == NSNumber::numberWithBool(nil, NO) => NSNumberValue::Bool(false),
+            Some(&b'C') | Some(&b'S') | Some(&b'I') | Some(&b'L') | Some(&b'Q') => {
+                NSNumberValue::Unsigned(self.unsignedLongLongValue())
+            }
+            Some(&b'f') | Some(&b'd') => NSNumberValue::Float(self.doubleValue()),
+            _ => NSNumberValue::Signed(self.longLongValue()),
+        }
+    }
+
+    unsafe fn get<S: NSNumberScalar>(self) -> Option<S> {
+        S::from_value(self.value())
+    }
+
+    // Comparing Numbers
+
+    unsafe fn compare_(self, other: id) -> NSComparisonResult {
+        msg_send![self.as_id(), compare:other]
+    }
+
+    unsafe fn isEqualToNumber_(self, other: id) -> BOOL {
+        msg_send![self.as_id(), isEqualToNumber:other]
+    }
+}
+
+/// The value in an `NSNumber`, as returned by `NSNumber::value`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum NSNumberValue {
+    Bool(bool),
+    Signed(i64),
+    Unsigned(u64),
+    Float(f64),
+}
+
+/// A Rust scalar that can be boxed in an `NSNumber`, with `Retained::<NSNumberRef>::from`,
+/// and unboxed with `NSNumber::get`.
+///
+/// Integers unbox from integers that fit, and from floats with no fractional part that
+/// fit. Floats unbox from any number but a `bool`, rounding if they must. `bool` only
+/// unboxes from a `bool`.
+pub trait NSNumberScalar: Copy {
+    /// Returns a new, retained number holding `self`.
+    unsafe fn new_number(self) -> id;
+
+    fn from_value(value: NSNumberValue) -> Option<Self>;
+}
+
+macro_rules! number_scalar {
+    ($($ty:ty => $init:ident),+) => {$(
+        impl NSNumberScalar for $ty {
+            unsafe fn new_number(self) -> id {
+                msg_send![NSNumber::alloc(nil), $init:self]
+            }
+
+            fn from_value(value: NSNumberValue) -> Option<$ty> {
+                // Every value that fits in a Rust integer fits in an `i128`.
+                let wide = match value {
+                    NSNumberValue::Bool(_) => return None,
+                    NSNumberValue::Signed(n) => n as i128,
+                    NSNumberValue::Unsigned(n) => n as i128,
+                    NSNumberValue::Float(f) => {
+                        let n = f as i128;
+                        if n as f64 != f {
+                            return None;
+                        }
+                        n
+                    }
+                };
+                let n = wide as $ty;
+                if n as i128 == wide { Some(n) } else { None }
+            }
+        }
+
+        impl From<$ty> for Retained<NSNumberRef> {
+            fn from(value: $ty) -> Retained<NSNumberRef> {
+                unsafe { Retained::new(NSNumberRef::from_id(value.new_number())) }
+            }
+        }
+    )+}
+}
+
+number_scalar! {
+    i8 => initWithChar,
+    u8 => initWithUnsignedChar,
+    i16 => initWithShort,
+    u16 => initWithUnsignedShort,
+    i32 => initWithInt,
+    u32 => initWithUnsignedInt,
+    i64 => initWithLongLong,
+    u64 => initWithUnsignedLongLong,
+    isize => initWithInteger,
+    usize => initWithUnsignedInteger
+}
+
+macro_rules! number_float {
+    ($($ty:ident => $init:ident),+) => {$(
+        impl NSNumberScalar for $ty {
+            unsafe fn new_number(self) -> id {
+                msg_send![NSNumber::alloc(nil), $init:self]
+            }
+
+            fn from_value(value: NSNumberValue) -> Option<$ty> {
+                match value {
+                    NSNumberValue::Bool(_) => None,
+                    NSNumberValue::Signed(n) => Some(n as $ty),
+                    NSNumberValue::Unsigned(n) => Some(n as $ty),
+                    NSNumberValue::Float(f) => Some(number_float!(@from_double $ty, f)),
+                }
+            }
+        }
+
+        impl From<$ty> for Retained<NSNumberRef> {
+            fn from(value: $ty) -> Retained<NSNumberRef> {
+                unsafe { Retained::new(NSNumberRef::from_id(value.new_number())) }
+            }
+        }
+    )+};
+    // Floating point values are read as doubles.
+    (@from_double f64, $f:expr) => { $f };
+    (@from_double f32, $f:expr) => { $f as f32 };
+}
+
+number_float! {
+    f32 => initWithFloat,
+    f64 => initWithDouble
+}
+
+impl NSNumberScalar for bool {
+    unsafe fn new_number(self) -> id {
+        msg_send![NSNumber::alloc(nil), initWithBool:if self { YES } else { NO }]
+    }
+
+    fn from_value(value: NSNumberValue) -> Option<bool> {
+        match value {
+            NSNumberValue::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Retained<NSNumberRef> {
+    fn from(value: bool) -> Retained<NSNumberRef> {
+        unsafe { Retained::new(NSNumberRef::from_id(value.new_number())) }
+    }
+}
+
+#[allow(non_upper_case_globals)]
+pub const NSIntegerMax: NSInteger = isize::MAX as NSInteger;
+/// The index returned when an object isn't found.
+#[allow(non_upper_case_globals)]
+pub const NSNotFound: NSUInteger = NSIntegerMax as NSUInteger;
+
+object_ref! {
+    pub struct NSArrayRef(NSArray): NSObjectRef;
+}
+
+pub trait NSArray {
+    unsafe fn alloc(_: Self) -> id {
+        msg_send![class!(NSArray), alloc]
+    }
+
+    unsafe fn array<'p>(_: Self, pool: &'p AutoreleasePool) -> Autoreleased<'p> {
+        pool.bind(msg_send![class!(NSArray), array])
+    }
+
+    unsafe fn arrayWithObjects<'p>(_: Self, pool: &'p AutoreleasePool,
+                                   objects: &[id]) -> Autoreleased<'p> {
+        pool.bind(msg_send![class!(NSArray), arrayWithObjects:objects.as_ptr() as *const libc::c_void
+                                                        count:objects.len()])
+    }
+
+    unsafe fn arrayWithObject<'p>(_: Self, pool: &'p AutoreleasePool,
+                                  object: id) -> Autoreleased<'p> {
+        pool.bind(msg_send![class!(NSArray), arrayWithObject:object])
+    }
+
+    unsafe fn initWithObjects_count_(self, objects: *const id, count: NSUInteger) -> id;
+
+    /// Returns a new array with `object` added at the end.
+    unsafe fn arrayByAddingObject_(self, object: id) -> id;
+    #[deprecated(note = "NSArray has no `arrayByAddingObjectFromArray:`; use `arrayByAddingObject_`")]
+    unsafe fn arrayByAddingObjectFromArray(self, object: id) -> id;
+    unsafe fn arrayByAddingObjectsFromArray(self, objects: id) -> id;
+
+    // Querying an Array
+    unsafe fn count(self) -> NSUInteger;
+    unsafe fn objectAtIndex_(self, index: NSUInteger) -> id;
+    unsafe fn firstObject(self) -> id;
+    unsafe fn lastObject(self) -> id;
+    unsafe fn containsObject_(self, object: id) -> BOOL;
+    /// The lowest index of an object equal to `object`, or `NSNotFound`.
+    unsafe fn indexOfObject_(self, object: id) -> NSUInteger;
+
+    /// Iterates over the array with `NSFastEnumeration`, retaining it until the iteration
+    /// finishes. The array must not be mutated while it is being iterated over.
+    unsafe fn iter(self) -> NSFastEnumerator;
+}
+
+impl<T: IsKindOf<NSArrayRef>> NSArray for T {
+    unsafe fn initWithObjects_count_(self, objects: *const id, count: NSUInteger) -> id {
+        msg_send![self.as_id(), initWithObjects:objects as *const libc::c_void count:count]
+    }
+
+    unsafe fn arrayByAddingObject_(self, object: id) -> id {
+        msg_send![self.as_id(), arrayByAddingObject:object]
+    }
+
+    unsafe fn arrayByAddingObjectFromArray(self, object: id) -> id {
+        self.arrayByAddingObject_(object)
+    }
+
+    unsafe fn arrayByAddingObjectsFromArray(self, objects: id) -> id {
+        msg_send![self.as_id(), arrayByAddingObjectsFromArray:objects]
+    }
+
+    // Querying an Array
+
+    unsafe fn count(self) -> NSUInteger {
+        msg_send![self.as_id(), count]
+    }
+
+    unsafe fn objectAtIndex_(self, index: NSUInteger) -> id {
+        msg_send![self.as_id(), objectAtIndex:index]
+    }
+
+    unsafe fn firstObject(self) -> id {
+        msg_send![self.as_id(), firstObject]
+    }
+
+    unsafe fn lastObject(self) -> id {
+        msg_send![self.as_id(), lastObject]
+    }
+
+    unsafe fn containsObject_(self, object: id) -> BOOL {
+        msg_send![self.as_id(), containsObject:object]
+    }
+
+    unsafe fn indexOfObject_(self, object: id) -> NSUInteger {
+        msg_send![self.as_id(), indexOfObject:object]
+    }
+
+    unsafe fn iter(self) -> NSFastEnumerator {
+        NSFastEnumerator::new(self.as_id())
+    }
+}
+
+impl<R: ReferenceCounting> IntoIterator for &Retained<NSArrayRef, R> {
+    type Item = id;
+    type IntoIter = NSFastEnumerator;
+
+    fn into_iter(self) -> NSFastEnumerator {
+        unsafe { self.iter() }
+    }
+}
+
+/// Builds an array of the objects, none of which may be nil.
+impl<T: AsId> FromIterator<T> for Retained<NSArrayRef> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Retained<NSArrayRef> {
+        let objects: Vec<id> = iter.into_iter().map(non_nil).collect();
+        unsafe {
+            let array = NSArray::alloc(nil).initWithObjects_count_(objects.as_ptr(),
+                                                                   objects.len() as NSUInteger);
+            Retained::new(NSArrayRef::from_id(array))
+        }
+    }
+}
+
+fn non_nil<T: AsId>(obj: T) -> id {
+    assert!(!obj.is_nil(), "collections can't contain nil");
+    obj.as_id()
+}
+
+object_ref! {
+    pub struct NSMutableArrayRef(NSMutableArray): NSArrayRef, NSObjectRef;
+}
+
+pub trait NSMutableArray {
+    unsafe fn alloc(_: Self) -> id {
+        msg_send![class!(NSMutableArray), alloc]
+    }
+
+    unsafe fn arrayWithCapacity<'p>(_: Self, pool: &'p AutoreleasePool,
+                                    capacity: NSUInteger) -> Autoreleased<'p> {
+        pool.bind(msg_send![class!(NSMutableArray), arrayWithCapacity:capacity])
+    }
+
+    unsafe fn initWithCapacity_(self, capacity: NSUInteger) -> id;
+
+    // Adding Objects
+    unsafe fn addObject_(self, object: id);
+    unsafe fn addObjectsFromArray_(self, objects: id);
+    unsafe fn insertObject_atIndex_(self, object: id, index: NSUInteger);
+
+    // Removing Objects
+    unsafe fn removeAllObjects(self);
+    unsafe fn removeLastObject(self);
+    unsafe fn removeObject_(self, object: id);
+    unsafe fn removeObjectAtIndex_(self, index: NSUInteger);
+
+    // Replacing Objects
+    unsafe fn replaceObjectAtIndex_withObject_(self, index: NSUInteger, object: id);
+    unsafe fn exchangeObjectAtIndex_withObjectAtIndex_(self, first: NSUInteger, second: NSUInteger);
+}
+
+impl<T: IsKindOf<NSMutableArrayRef>> NSMutableArray for T {
+    unsafe fn initWithCapacity_(self, capacity: NSUInteger) -> id {
+        msg_send![self.as_id(), initWithCapacity:capacity]
+    }
+
+    // Adding Objects
+
+    unsafe fn addObject_(self, object: id) {
+        msg_send![self.as_id(), addObject:object]
+    }
+
+    unsafe fn addObjectsFromArray_(self, objects: id) {
+        msg_send![self.as_id(), addObjectsFromArray:objects]
+    }
+
+    unsafe fn insertObject_atIndex_(self, object: id, index: NSUInteger) {
+        msg_send![self.as_id(), insertObject:object atIndex:index]
+    }
+
+    // Removing Objects
+
+    unsafe fn removeAllObjects(self) {
+        msg_send![self.as_id(), removeAllObjects]
+    }
+
+    unsafe fn removeLastObject(self) {
+        msg_send![self.as_id(), removeLastObject]
+    }
+
+    unsafe fn removeObject_(self, object: id) {
+        msg_send![self.as_id(), removeObject:object]
+    }
+
+    unsafe fn removeObjectAtIndex_(self, index: NSUInteger) {
+        msg_send![self.as_id(), removeObjectAtIndex:index]
+    }
+
+    // Replacing Objects
+
+    unsafe fn replaceObjectAtIndex_withObject_(self, index: NSUInteger, object: id) {
+        msg_send![self.as_id(), replaceObjectAtIndex:index withObject:object]
+    }
+
+    unsafe fn exchangeObjectAtIndex_withObjectAtIndex_(self, first: NSUInteger, second: NSUInteger) {
+        msg_send![self.as_id(), exchangeObjectAtIndex:first withObjectAtIndex:second]
+    }
+}
+
+impl<R: ReferenceCounting> IntoIterator for &Retained<NSMutableArrayRef, R> {
+    type Item = id;
+    type IntoIter = NSFastEnumerator;
+
+    fn into_iter(self) -> NSFastEnumerator {
+        unsafe { self.iter() }
+    }
+}
+
+/// Builds a mutable array of the objects, none of which may be nil.
+impl<T: AsId> FromIterator<T> for Retained<NSMutableArrayRef> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Retained<NSMutableArrayRef> {
+        let iter = iter.into_iter();
+        unsafe {
+            let capacity = iter.size_hint().0 as NSUInteger;
+            let array = NSMutableArrayRef::from_id(NSMutableArray::alloc(nil));
+            let array = array.initWithCapacity_(capacity);
+            let array = Retained::new(NSMutableArrayRef::from_id(array));
+            for obj in iter {
+                array.addObject_(non_nil(obj));
+            }
+            array
+        }
+    }
+}
+
+/// The state `countByEnumeratingWithState:objects:count:` keeps between calls.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct NSFastEnumerationState {
+    pub state: libc::c_ulong,
+    pub itemsPtr: *mut id,
+    pub mutationsPtr: *mut libc::c_ulong,
+    pub extra: [libc::c_ulong; 5],
+}
+
+/// An iterator over a collection that conforms to `NSFastEnumeration`, which fetches
+/// the objects in batches. Like `for`-`in` in Objective-C, it panics if the collection
+/// is mutated while being iterated over.
+pub struct NSFastEnumerator {
+    // Retained, so that safe code can't free the collection while it's being iterated
+    // over. Released as soon as the iteration finishes.
+    collection: StrongPtr,
+    state: NSFastEnumerationState,
+    // Boxed, since `state.itemsPtr` may point into it.
+    buffer: Box<[id; 16]>,
+    index: usize,
+    count: usize,
+    mutations: Option<libc::c_ulong>,
+}
+
+impl NSFastEnumerator {
+    /// Iterates over `collection`, which must conform to `NSFastEnumeration`. The
+    /// collection is retained until the iteration finishes or the iterator is dropped. A
+    /// nil collection is empty.
+    pub unsafe fn new(collection: id) -> NSFastEnumerator {
+        NSFastEnumerator {
+            collection: StrongPtr::retain(collection),
+            state: NSFastEnumerationState {
+                state: 0,
+                itemsPtr: ptr::null_mut(),
+                mutationsPtr: ptr::null_mut(),
+                extra: [0; 5],
+            },
+            buffer: Box::new([nil; 16]),
+            index: 0,
+            count: 0,
+            mutations: None,
+        }
+    }
+}
+
+impl Iterator for NSFastEnumerator {
+    type Item = id;
+
+    fn next(&mut self) -> Option<id> {
+        if self.index == self.count {
+            if self.collection.is_null() {
+                return None;
+            }
+            let count: NSUInteger = unsafe {
+                msg_send![*self.collection,
+                          countByEnumeratingWithState:&mut self.state as *mut _ as *mut libc::c_void
+                                              objects:self.buffer.as_mut_ptr() as *mut libc::c_void
+                                                count:self.buffer.len() as NSUInteger]
+            };
+            if count == 0 {
+                self.collection = unsafe { StrongPtr::new(nil) };
+                return None;
+            }
+            self.index = 0;
+            self.count = count as usize;
+        }
+        unsafe {
+            if !self.state.mutationsPtr.is_null() {
+                let mutations = *self.state.mutationsPtr;
+                if *self.mutations.get_or_insert(mutations) != mutations {
+                    panic!("collection was mutated while being iterated over");
+                }
+            }
+            let obj = *self.state.itemsPtr.add(self.index);
+            self.index += 1;
+            Some(obj)
+        }
+    }
+}
+
+object_ref! {
+    pub struct NSDictionaryRef(NSDictionary): NSObjectRef;
+}
+
+pub trait NSDictionary {
+    unsafe fn alloc(_: Self) -> id {
+        msg_send![class!(NSDictionary), alloc]
+    }
+
+    unsafe fn dictionary<'p>(_: Self, pool: &'p AutoreleasePool) -> Autoreleased<'p> {
+        pool.bind(msg_send![class!(NSDictionary), dictionary])
+    }
+
+    /// `objects` and `keys` must be the same length.
+    unsafe fn dictionaryWithObjects_forKeys_<'p>(_: Self, pool: &'p AutoreleasePool,
+                                                 objects: &[id], keys: &[id]) -> Autoreleased<'p> {
+        assert_eq!(objects.len(), keys.len(), "there must be a key for each object");
+        pool.bind(msg_send![class!(NSDictionary), dictionaryWithObjects:objects.as_ptr() as *const libc::c_void
+                                                                forKeys:keys.as_ptr() as *const libc::c_void
+                                                                  count:objects.len() as NSUInteger])
+    }
+
+    unsafe fn dictionaryWithObject_forKey_<'p>(_: Self, pool: &'p AutoreleasePool,
+                                               object: id, key: id) -> Autoreleased<'p> {
+        pool.bind(msg_send![class!(NSDictionary), dictionaryWithObject:object forKey:key])
+    }
+
+    unsafe fn initWithObjects_forKeys_count_(self, objects: *const id, keys: *const id,
+                                             count: NSUInteger) -> id;
+
+    // Counting Entries
+    unsafe fn count(self) -> NSUInteger;
+
+    // Accessing Keys and Values
+    /// The object for `key`, or nil if there isn't one.
+    unsafe fn objectForKey_(self, key: id) -> id;
+    /// The object for the string key `key`, or nil if there isn't one.
+    unsafe fn get_str(self, key: &str) -> id;
+    unsafe fn allKeys(self) -> id;
+    unsafe fn allValues(self) -> id;
+
+    // Enumerating Dictionaries
+    /// Iterates over the keys with `NSFastEnumeration`. Like all of these iterators, it
+    /// retains the dictionary, which must not be mutated while it is being iterated over.
+    unsafe fn keys(self) -> NSFastEnumerator;
+    unsafe fn values(self) -> NSDictionaryValues;
+    unsafe fn iter(self) -> NSDictionaryIter;
+
+    /// Copies the entries into a `HashMap`. The values aren't retained. Keys which aren't
+    /// strings, such as `NSNumber`s, are converted with their `description`; if two
+    /// keys convert to the same string, only one of their entries is kept.
+    unsafe fn to_hash_map(self) -> HashMap<String, id>;
+}
+
+impl<T: IsKindOf<NSDictionaryRef>> NSDictionary for T {
+    unsafe fn initWithObjects_forKeys_count_(self, objects: *const id, keys: *const id,
+                                             count: NSUInteger) -> id {
+        msg_send![self.as_id(), initWithObjects:objects as *const libc::c_void
+                                        forKeys:keys as *const libc::c_void
+                                          count:count]
+    }
+
+    // Counting Entries
+
+    unsafe fn count(self) -> NSUInteger {
+        msg_send![self.as_id(), count]
+    }
+
+    // Accessing Keys and Values
+
+    unsafe fn objectForKey_(self, key: id) -> id {
+        msg_send![self.as_id(), objectForKey:key]
+    }
+
+    unsafe fn get_str(self, key: &str) -> id {
+        let key = Retained::<NSStringRef>::from(key);
+        self.objectForKey_(key.as_id())
+    }
+
+    unsafe fn allKeys(self) -> id {
+        msg_send![self.as_id(), allKeys]
+    }
+
+    unsafe fn allValues(self) -> id {
+        msg_send![self.as_id(), allValues]
+    }
+
+    // Enumerating Dictionaries
+
+    unsafe fn keys(self) -> NSFastEnumerator {
+        NSFastEnumerator::new(self.as_id())
+    }
+
+    unsafe fn values(self) -> NSDictionaryValues {
+        NSDictionaryValues(self.iter())
+    }
+
+    unsafe fn iter(self) -> NSDictionaryIter {
+        NSDictionaryIter {
+            dictionary: StrongPtr::retain(self.as_id()),
+            keys: self.keys(),
+        }
+    }
+
+    unsafe fn to_hash_map(self) -> HashMap<String, id> {
+        self.iter().map(|(key, value)| {
+            let key = match NSStringRef::downcast(key) {
+                Some(key) => key,
+                None => NSStringRef::from_id(msg_send![key, description]),
+            };
+            (key.to_str().into_owned(), value)
+        }).collect()
+    }
+}
+
+/// An iterator over the keys and values of a dictionary.
+pub struct NSDictionaryIter {
+    dictionary: StrongPtr,
+    keys: NSFastEnumerator,
+}
+
+impl Iterator for NSDictionaryIter {
+    type Item = (id, id);
+
+    fn next(&mut self) -> Option<(id, id)> {
+        let key = self.keys.next()?;
+        Some((key, unsafe { (*self.dictionary).objectForKey_(key) }))
+    }
+}
+
+/// An iterator over the values of a dictionary.
+pub struct NSDictionaryValues(NSDictionaryIter);
+
+impl Iterator for NSDictionaryValues {
+    type Item = id;
+
+    fn next(&mut self) -> Option<id> {
+        self.0.next().map(|(_, value)| value)
+    }
+}
+
+impl<R: ReferenceCounting> IntoIterator for &Retained<NSDictionaryRef, R> {
+    type Item = (id, id);
+    type IntoIter = NSDictionaryIter;
+
+    fn into_iter(self) -> NSDictionaryIter {
+        unsafe { self.iter() }
+    }
+}
+
+/// Builds a dictionary of the keys and values, none of which may be nil. The keys are
+/// copied, and later keys replace earlier equal ones.
+impl<K: AsId, V: AsId> FromIterator<(K, V)> for Retained<NSDictionaryRef> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Retained<NSDictionaryRef> {
+        let (keys, objects): (Vec<id>, Vec<id>) = iter.into_iter().map(|(key, value)| {
+            (non_nil(key), non_nil(value))
+        }).unzip();
+        unsafe {
+            let dictionary = NSDictionary::alloc(nil)
+                .initWithObjects_forKeys_count_(objects.as_ptr(), keys.as_ptr(),
+                                                objects.len() as NSUInteger);
+            Retained::new(NSDictionaryRef::from_id(dictionary))
+        }
+    }
+}
+
+/// Builds a dictionary with string keys. None of the values may be nil.
+impl<'a, K, V, S> From<&'a HashMap<K, V, S>> for Retained<NSDictionaryRef>
+        where K: AsRef<str> + Eq + Hash, V: AsId, S: BuildHasher {
+    fn from(map: &'a HashMap<K, V, S>) -> Retained<NSDictionaryRef> {
+        let entries: Vec<(Retained<NSStringRef>, V)> = map.iter().map(|(key, &value)| {
+            (key.as_ref().into(), value)
+        }).collect();
+        entries.iter().map(|&(ref key, value)| (**key, value)).collect()
+    }
+}
+
+object_ref! {
+    pub struct NSMutableDictionaryRef(NSMutableDictionary): NSDictionaryRef, NSObjectRef;
+}
+
+pub trait NSMutableDictionary {
+    unsafe fn alloc(_: Self) -> id {
+        msg_send![class!(NSMutableDictionary), alloc]
+    }
+
+    unsafe fn dictionaryWithCapacity<'p>(_: Self, pool: &'p AutoreleasePool,
+                                         capacity: NSUInteger) -> Autoreleased<'p> {
+        pool.bind(msg_send![class!(NSMutableDictionary), dictionaryWithCapacity:capacity])
+    }
+
+    unsafe fn initWithCapacity_(self, capacity: NSUInteger) -> id;
+
+    // Adding Entries
+    /// Sets the object for a copy of `key`, replacing any object it already has.
+    unsafe fn setObject_forKey_(self, object: id, key: id);
+    unsafe fn addEntriesFromDictionary_(self, dictionary: id);
+
+    // Removing Entries
+    unsafe fn removeObjectForKey_(self, key: id);
+    unsafe fn removeAllObjects(self);
+}
+
+impl<T: IsKindOf<NSMutableDictionaryRef>> NSMutableDictionary for T {
+    unsafe fn initWithCapacity_(self, capacity: NSUInteger) -> id {
+        msg_send![self.as_id(), initWithCapacity:capacity]
+    }
+
+    // Adding Entries
+
+    unsafe fn setObject_forKey_(self, object: id, key: id) {
+        msg_send![self.as_id(), setObject:object forKey:key]
+    }
+
+    unsafe fn addEntriesFromDictionary_(self, dictionary: id) {
+        msg_send![self.as_id(), addEntriesFromDictionary:dictionary]
+    }
+
+    // Removing Entries
+
+    unsafe fn removeObjectForKey_(self, key: id) {
+        msg_send![self.as_id(), removeObjectForKey:key]
+    }
+
+    unsafe fn removeAllObjects(self) {
+        msg_send![self.as_id(), removeAllObjects]
+    }
+}
+
+impl<R: ReferenceCounting> IntoIterator for &Retained<NSMutableDictionaryRef, R> {
+    type Item = (id, id);
+    type IntoIter = NSDictionaryIter;
+
+    fn into_iter(self) -> NSDictionaryIter {
+        unsafe { self.iter() }
+    }
+}
+
+/// Builds a mutable dictionary of the keys and values, none of which may be nil.
+impl<K: AsId, V: AsId> FromIterator<(K, V)> for Retained<NSMutableDictionaryRef> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Retained<NSMutableDictionaryRef> {
+        let iter = iter.into_iter();
+        unsafe {
+            let capacity = iter.size_hint().0 as NSUInteger;
+            let dictionary = NSMutableDictionaryRef::from_id(NSMutableDictionary::alloc(nil));
+            let dictionary = dictionary.initWithCapacity_(capacity);
+            let dictionary = Retained::new(NSMutableDictionaryRef::from_id(dictionary));
+            for (key, value) in iter {
+                dictionary.setObject_forKey_(non_nil(value), non_nil(key));
+            }
+            dictionary
+        }
+    }
+}
+
+/// Builds a mutable dictionary with string keys. None of the values may be nil.
+impl<'a, K, V, S> From<&'a HashMap<K, V, S>> for Retained<NSMutableDictionaryRef>
+        where K: AsRef<str> + Eq + Hash, V: AsId, S: BuildHasher {
+    fn from(map: &'a HashMap<K, V, S>) -> Retained<NSMutableDictionaryRef> {
+        let dictionary: Retained<NSMutableDictionaryRef> = unsafe {
+            let dictionary = NSMutableDictionaryRef::from_id(NSMutableDictionary::alloc(nil));
+            let dictionary = dictionary.initWithCapacity_(map.len() as NSUInteger);
+            Retained::new(NSMutableDictionaryRef::from_id(dictionary))
+        };
+        for (key, &value) in map {
+            let key = Retained::<NSStringRef>::from(key.as_ref());
+            unsafe { dictionary.setObject_forKey_(non_nil(value), key.as_id()) };
+        }
+        dictionary
+    }
+}
+
+object_ref! {
+    pub struct NSDataRef(NSData): NSObjectRef;
+}
+
+pub trait NSData {
+    unsafe fn alloc(_: Self) -> id {
+        msg_send![class!(NSData), alloc]
+    }
+
+    unsafe fn data<'p>(_: Self, pool: &'p AutoreleasePool) -> Autoreleased<'p> {
+        pool.bind(msg_send![class!(NSData), data])
+    }
+
+    /// Returns new data holding a copy of the bytes.
+    unsafe fn dataWithBytes_length_<'p>(_: Self, pool: &'p AutoreleasePool,
+                                        bytes: *const libc::c_void,
+                                        length: NSUInteger) -> Autoreleased<'p> {
+        pool.bind(msg_send![class!(NSData), dataWithBytes:bytes length:length])
+    }
+
+    unsafe fn initWithBytes_length_(self, bytes: *const libc::c_void, length: NSUInteger) -> id;
+    /// Wraps the bytes without copying them. If `freeWhenDone` is `YES` they must have
+    /// been allocated with `malloc`; otherwise they must outlive the data.
+    unsafe fn initWithBytesNoCopy_length_freeWhenDone_(self, bytes: *mut libc::c_void,
+                                                       length: NSUInteger,
+                                                       freeWhenDone: BOOL) -> id;
+
+    // Accessing Underlying Bytes
+    unsafe fn bytes(self) -> *const libc::c_void;
+    unsafe fn length(self) -> NSUInteger;
+    /// Borrows the bytes for as long as this reference to the data. The data must not be
+    /// mutated or deallocated while the slice is in use.
+    unsafe fn as_slice(&self) -> &[u8];
+    unsafe fn to_vec(self) -> Vec<u8>;
+
+    unsafe fn isEqualToData_(self, other: id) -> BOOL;
+}
+
+impl<T: IsKindOf<NSDataRef>> NSData for T {
+    unsafe fn initWithBytes_length_(self, bytes: *const libc::c_void, length: NSUInteger) -> id {
+        msg_send![self.as_id(), initWithBytes:bytes length:length]
+    }
+
+    unsafe fn initWithBytesNoCopy_length_freeWhenDone_(self, bytes: *mut libc::c_void,
+                                                       length: NSUInteger,
+                                                       freeWhenDone: BOOL) -> id {
+        msg_send![self.as_id(), initWithBytesNoCopy:bytes length:length freeWhenDone:freeWhenDone]
+    }
+
+    // Accessing Underlying Bytes
+
+    unsafe fn bytes(self) -> *const libc::c_void {
+        msg_send![self.as_id(), bytes]
+    }
+
+    unsafe fn length(self) -> NSUInteger {
+        msg_send![self.as_id(), length]
+    }
+
+    unsafe fn as_slice(&self) -> &[u8] {
+        // Empty data, and nil, may have null bytes.
+        let length = if self.is_nil() { 0 } else { self.length() as usize };
+        if length == 0 {
+            return &[];
+        }
+        slice::from_raw_parts(self.bytes() as *const u8, length)
+    }
+
+    unsafe fn to_vec(self) -> Vec<u8> {
+        self.as_slice().to_vec()
+    }
+
+    unsafe fn isEqualToData_(self, other: id) -> BOOL {
+        msg_send![self.as_id(), isEqualToData:other]
+    }
+}
+
+/// Copies the bytes.
+impl<'a> From<&'a [u8]> for Retained<NSDataRef> {
+    fn from(bytes: &'a [u8]) -> Retained<NSDataRef> {
+        unsafe {
+            let data = NSDataRef::from_id(NSData::alloc(nil))
+                .initWithBytes_length_(bytes.as_ptr() as *const libc::c_void,
+                                       bytes.len() as NSUInteger);
+            Retained::new(NSDataRef::from_id(data))
+        }
+    }
+}
+
+/// Takes ownership of the vector's buffer without copying it. The buffer is freed when
+/// the data is deallocated.
+impl From<Vec<u8>> for Retained<NSDataRef> {
+    fn from(mut bytes: Vec<u8>) -> Retained<NSDataRef> {
+        if bytes.capacity() == 0 {
+            return Retained::from(&bytes[..]);
+        }
+        let ptr = bytes.as_mut_ptr() as *mut libc::c_void;
+        let length = bytes.len() as NSUInteger;
+        // The block owns the vector, so its buffer is freed when NSData calls the block,
+        // or when the last copy of the block is released if init fails.
+        let bytes = Cell::new(Some(bytes));
+        let deallocator = ConcreteBlock::new(move |_: *mut libc::c_void, _: NSUInteger| {
+            drop(bytes.take());
+        }).copy();
+        unsafe {
+            let data: id = msg_send![NSData::alloc(nil),
+                                     initWithBytesNoCopy:ptr
+                                                  length:length
+                                             deallocator:BlockArgument(&deallocator)];
+            Retained::new(NSDataRef::from_id(data))
+        }
+    }
+}
+
+impl<'a, R: ReferenceCounting> From<&'a Retained<NSDataRef, R>> for Vec<u8> {
+    fn from(data: &'a Retained<NSDataRef, R>) -> Vec<u8> {
+        unsafe { data.to_vec() }
+    }
+}
+
+// Passes a block as a message argument, which the runtime encodes as `@?`.
+#[repr(transparent)]
+struct BlockArgument<'a, A: 'a, R: 'a>(&'a Block<A, R>);
+
+unsafe impl<'a, A, R> objc::Encode for BlockArgument<'a, A, R> {
+    fn encode() -> objc::Encoding {
+        unsafe { objc::Encoding::from_str("@?") }
+    }
+}
+
+object_ref! {
+    pub struct NSMutableDataRef(NSMutableData): NSDataRef, NSObjectRef;
+}
+
+pub trait NSMutableData {
+    unsafe fn alloc(_: Self) -> id {
+        msg_send![class!(NSMutableData), alloc]
+    }
+
+    unsafe fn dataWithCapacity<'p>(_: Self, pool: &'p AutoreleasePool,
+                                   capacity: NSUInteger) -> Autoreleased<'p> {
+        pool.bind(msg_send![class!(NSMutableData), dataWithCapacity:capacity])
+    }
+
+    unsafe fn initWithCapacity_(self, capacity: NSUInteger) -> id;
+
+    // Accessing Raw Bytes
+    unsafe fn mutableBytes(self) -> *mut libc::c_void;
+    // Adjusting Capacity
+    /// Sets the length, filling any added bytes with zeros.
+    unsafe fn setLength_(self, length: NSUInteger);
+    unsafe fn increaseLengthBy_(self, extraLength: NSUInteger);
+
+    // Adding Bytes
+    unsafe fn appendBytes_length_(self, bytes: *const libc::c_void, length: NSUInteger);
+    unsafe fn appendData_(self, other: id);
+    unsafe fn extend_from_slice(self, bytes: &[u8]);
+
+    // Modifying Bytes
+    unsafe fn replaceBytesInRange_withBytes_(self, range: NSRange, bytes: *const libc::c_void);
+    unsafe fn resetBytesInRange_(self, range: NSRange);
+}
+
+impl<T: IsKindOf<NSMutableDataRef>> NSMutableData for T {
+    unsafe fn initWithCapacity_(self, capacity: NSUInteger) -> id {
+        msg_send![self.as_id(), initWithCapacity:capacity]
+    }
+
+    // Accessing Raw Bytes
+
+    unsafe fn mutableBytes(self) -> *mut libc::c_void {
+        msg_send![self.as_id(), mutableBytes]
+    }
+
+    // Adjusting Capacity
+
+    unsafe fn setLength_(self, length: NSUInteger) {
+        msg_send![self.as_id(), setLength:length]
+    }
+
+    unsafe fn increaseLengthBy_(self, extraLength: NSUInteger) {
+        msg_send![self.as_id(), increaseLengthBy:extraLength]
+    }
+
+    // Adding Bytes
+
+    unsafe fn appendBytes_length_(self, bytes: *const libc::c_void, length: NSUInteger) {
+        msg_send![self.as_id(), appendBytes:bytes length:length]
+    }
+
+    unsafe fn appendData_(self, other: id) {
+        msg_send![self.as_id(), appendData:other]
+    }
+
+    unsafe fn extend_from_slice(self, bytes: &[u8]) {
+        self.appendBytes_length_(bytes.as_ptr() as *const libc::c_void, bytes.len() as NSUInteger)
+    }
+
+    // Modifying Bytes
+
+    unsafe fn replaceBytesInRange_withBytes_(self, range: NSRange, bytes: *const libc::c_void) {
+        msg_send![self.as_id(), replaceBytesInRange:range withBytes:bytes]
+    }
+
+    unsafe fn resetBytesInRange_(self, range: NSRange) {
+        msg_send![self.as_id(), resetBytesInRange:range]
+    }
+}
+
+/// Copies the bytes.
+impl<'a> From<&'a [u8]> for Retained<NSMutableDataRef> {
+    fn from(bytes: &'a [u8]) -> Retained<NSMutableDataRef> {
+        unsafe {
+            let data = NSMutableDataRef::from_id(NSMutableData::alloc(nil))
+                .initWithBytes_length_(bytes.as_ptr() as *const libc::c_void,
+                                       bytes.len() as NSUInteger);
+            Retained::new(NSMutableDataRef::from_id(data))
+        }
+    }
+}
+
+impl<R: ReferenceCounting> Retained<NSMutableDataRef, R> {
+    /// Borrows the bytes mutably for as long as this reference to the data. The data must
+    /// not be accessed through other references, resized or deallocated while the slice
+    /// is in use.
+    pub unsafe fn as_mut_slice(&mut self) -> &mut [u8] {
+        let length = if self.is_nil() { 0 } else { NSDataRef::from_id(self.as_id()).length() as usize };
+        if length == 0 {
+            return &mut [];
+        }
+        slice::from_raw_parts_mut(self.mutableBytes() as *mut u8, length)
+    }
+}
+
+object_ref! {
+    pub struct NSURLRef(NSURL): NSObjectRef;
+}
+
+pub trait NSURL {
+    unsafe fn alloc(_: Self) -> id {
+        msg_send![class!(NSURL), alloc]
+    }
+
+    /// Returns nil if `string` isn't a valid URL.
+    unsafe fn URLWithString_(_: Self, string: id) -> id {
+        msg_send![class!(NSURL), URLWithString:string]
+    }
+
+    unsafe fn URLWithString_relativeToURL_(_: Self, string: id, baseURL: id) -> id {
+        msg_send![class!(NSURL), URLWithString:string relativeToURL:baseURL]
+    }
+
+    /// Checks the file system to find out whether `path` is a directory.
+    unsafe fn fileURLWithPath_(_: Self, path: id) -> id {
+        msg_send![class!(NSURL), fileURLWithPath:path]
+    }
+
+    unsafe fn fileURLWithPath_isDirectory_(_: Self, path: id, isDir: BOOL) -> id {
+        msg_send![class!(NSURL), fileURLWithPath:path isDirectory:isDir]
+    }
+
+    /// Takes the path as the bytes the file system uses, which needn't be UTF-8.
+    unsafe fn fileURLWithFileSystemRepresentation_isDirectory_relativeToURL_(
+            _: Self, path: *const libc::c_char, isDir: BOOL, baseURL: id) -> id {
+        msg_send![class!(NSURL), fileURLWithFileSystemRepresentation:path
+                                                         isDirectory:isDir
+                                                       relativeToURL:baseURL]
+    }
+
+    unsafe fn initWithString_(self, string: id) -> id;
+    unsafe fn initFileURLWithPath_(self, path: id) -> id;
+    unsafe fn initFileURLWithFileSystemRepresentation_isDirectory_relativeToURL_(
+            self, path: *const libc::c_char, isDir: BOOL, baseURL: id) -> id;
+
+    // Accessing the Parts of the URL
+    unsafe fn absoluteString(self) -> id;
+    unsafe fn absoluteURL(self) -> id;
+    unsafe fn baseURL(self) -> id;
+    unsafe fn scheme(self) -> id;
+    unsafe fn user(self) -> id;
+    unsafe fn password(self) -> id;
+    unsafe fn host(self) -> id;
+    /// An `NSNumber`, or nil if the URL has no port.
+    unsafe fn port(self) -> id;
+    /// The percent-decoded path.
+    unsafe fn path(self) -> id;
+    unsafe fn query(self) -> id;
+    unsafe fn fragment(self) -> id;
+    unsafe fn lastPathComponent(self) -> id;
+    unsafe fn pathExtension(self) -> id;
+    /// The path in the file system's representation, which lives as long as the
+    /// current autorelease pool.
+    unsafe fn fileSystemRepresentation(self) -> *const libc::c_char;
+
+    // Modifying and Converting a File URL
+    unsafe fn isFileURL(self) -> BOOL;
+    unsafe fn isFileReferenceURL(self) -> BOOL;
+    /// A URL that refers to the file by its ID, and keeps referring to it if it's moved.
+    unsafe fn fileReferenceURL(self) -> id;
+    /// Resolves a file reference URL to a path URL, or returns nil if the file no longer
+    /// exists.
+    unsafe fn filePathURL(self) -> id;
+    unsafe fn URLByAppendingPathComponent_(self, component: id) -> id;
+    unsafe fn URLByDeletingLastPathComponent(self) -> id;
+    unsafe fn URLByResolvingSymlinksInPath(self) -> id;
+    unsafe fn URLByStandardizingPath(self) -> id;
+
+    /// The path of a file URL, resolving it first if it's a file reference URL, or
+    /// `None` if it isn't a file URL or refers to a file that no longer exists.
+    unsafe fn to_path_buf(self) -> Option<PathBuf>;
+}
+
+impl<T: IsKindOf<NSURLRef>> NSURL for T {
+    unsafe fn initWithString_(self, string: id) -> id {
+        msg_send![self.as_id(), initWithString:string]
+    }
+
+    unsafe fn initFileURLWithPath_(self, path: id) -> id {
+        msg_send![self.as_id(), initFileURLWithPath:path]
+    }
+
+    unsafe fn initFileURLWithFileSystemRepresentation_isDirectory_relativeToURL_(
+            self, path: *const libc::c_char, isDir: BOOL, baseURL: id) -> id {
+        msg_send![self.as_id(), initFileURLWithFileSystemRepresentation:path
+                                                            isDirectory:isDir
+                                                          relativeToURL:baseURL]
+    }
+
+    // Accessing the Parts of the URL
+
+    unsafe fn absoluteString(self) -> id {
+        msg_send![self.as_id(), absoluteString]
+    }
+
+    unsafe fn absoluteURL(self) -> id {
+        msg_send![self.as_id(), absoluteURL]
+    }
+
+    unsafe fn baseURL(self) -> id {
+        msg_send![self.as_id(), baseURL]
+    }
+
+    unsafe fn scheme(self) -> id {
+        msg_send![self.as_id(), scheme]
+    }
+
+    unsafe fn user(self) -> id {
+        msg_send![self.as_id(), user]
+    }
+
+    unsafe fn password(self) -> id {
+        msg_send![self.as_id(), password]
+    }
+
+    unsafe fn host(self) -> id {
+        msg_send![self.as_id(), host]
+    }
+
+    unsafe fn port(self) -> id {
+        msg_send![self.as_id(), port]
+    }
+
+    unsafe fn path(self) -> id {
+        msg_send![self.as_id(), path]
+    }
+
+    unsafe fn query(self) -> id {
+        msg_send![self.as_id(), query]
+    }
+
+    unsafe fn fragment(self) -> id {
+        msg_send![self.as_id(), fragment]
+    }
+
+    unsafe fn lastPathComponent(self) -> id {
+        msg_send![self.as_id(), lastPathComponent]
+    }
+
+    unsafe fn pathExtension(self) -> id {
+        msg_send![self.as_id(), pathExtension]
+    }
+
+    unsafe fn fileSystemRepresentation(self) -> *const libc::c_char {
+        msg_send![self.as_id(), fileSystemRepresentation]
+    }
+
+    // Modifying and Converting a File URL
+
+    unsafe fn isFileURL(self) -> BOOL {
+        msg_send![self.as_id(), isFileURL]
+    }
+
+    unsafe fn isFileReferenceURL(self) -> BOOL {
+        msg_send![self.as_id(), isFileReferenceURL]
+    }
+
+    unsafe fn fileReferenceURL(self) -> id {
+        msg_send![self.as_id(), fileReferenceURL]
+    }
+
+    unsafe fn filePathURL(self) -> id {
+        msg_send![self.as_id(), filePathURL]
+    }
+
+    unsafe fn URLByAppendingPathComponent_(self, component: id) -> id {
+        msg_send![self.as_id(), URLByAppendingPathComponent:component]
+    }
+
+    unsafe fn URLByDeletingLastPathComponent(self) -> id {
+        msg_send![self.as_id(), URLByDeletingLastPathComponent]
+    }
+
+    unsafe fn URLByResolvingSymlinksInPath(self) -> id {
+        msg_send![self.as_id(), URLByResolvingSymlinksInPath]
+    }
+
+    unsafe fn URLByStandardizingPath(self) -> id {
+        msg_send![self.as_id(), URLByStandardizingPath]
+    }
+
+    unsafe fn to_path_buf(self) -> Option<PathBuf> {
+        if self.is_nil() || self.isFileURL() == NO {
+            return None;
+        }
+        let url = if self.isFileReferenceURL() != NO {
+            NSURLRef::from_id(self.filePathURL())
+        } else {
+            NSURLRef::from_id(self.as_id())
+        };
+        if url.is_nil() {
+            return None;
+        }
+        let string = NSStringRef::from_id(NSURLRef::from_id(url.absoluteURL()).absoluteString());
+        let path = file_url_path(string.to_str().as_bytes())?;
+        Some(PathBuf::from(OsString::from_vec(path)))
+    }
+}
+
+// The path of a `file:` URL string, with its percent escapes decoded byte for byte.
+// Unlike `fileSystemRepresentation`, which converts the path to decomposed Unicode, this
+// gives back exactly the bytes that were encoded, even if they aren't UTF-8.
+fn file_url_path(url: &[u8]) -> Option<Vec<u8>> {
+    let scheme = b"file://";
+    if url.len() < scheme.len() || !url[..scheme.len()].eq_ignore_ascii_case(scheme) {
+        return None;
+    }
+    // Skip the host, which is empty or `localhost`.
+    let rest = &url[scheme.len()..];
+    let rest = &rest[rest.iter().position(|&b| b == b'/')?..];
+    let rest = &rest[..rest.iter().position(|&b| b == b'?' || b == b'#').unwrap_or(rest.len())];
+
+    fn hex(b: u8) -> Option<u8> {
+        (b as char).to_digit(16).map(|digit| digit as u8)
+    }
+
+    let mut path = Vec::with_capacity(rest.len());
+    let mut i = 0;
+    while i < rest.len() {
+        let escaped = if rest[i] == b'%' && i + 2 < rest.len() {
+            hex(rest[i + 1]).and_then(|high| hex(rest[i + 2]).map(|low| high << 4 | low))
+        } else {
+            None
+        };
+        match escaped {
+            Some(b) => {
+                path.push(b);
+                i += 3;
+            }
+            None => {
+                path.push(rest[i]);
+                i += 1;
+            }
+        }
+    }
+    Some(path)
+}
+
+/// Returns a file URL for the path, passing its bytes through unchanged so that paths
+/// which aren't UTF-8 are kept. A relative path is relative to the current directory.
+///
+/// # Panics
+///
+/// Panics if the path contains a nul byte.
+impl<'a> From<&'a Path> for Retained<NSURLRef> {
+    fn from(path: &'a Path) -> Retained<NSURLRef> {
+        let bytes = CString::new(path.as_os_str().as_bytes()).expect("path contains a nul byte");
+        let is_dir = if path.is_dir() { YES } else { NO };
+        unsafe {
+            let url = NSURLRef::from_id(NSURL::alloc(nil))
+                .initFileURLWithFileSystemRepresentation_isDirectory_relativeToURL_(
+                    bytes.as_ptr(), is_dir, nil);
+            Retained::new(NSURLRef::from_id(url))
+        }
+    }
+}
+
+impl FromStr for Retained<NSURLRef> {
+    type Err = ParseURLError;
+
+    fn from_str(s: &str) -> Result<Retained<NSURLRef>, ParseURLError> {
+        let string = Retained::<NSStringRef>::from(s);
+        unsafe {
+            let url = NSURLRef::from_id(NSURL::alloc(nil)).initWithString_(string.as_id());
+            if url.is_null() {
+                Err(ParseURLError { _priv: () })
+            } else {
+                Ok(Retained::new(NSURLRef::from_id(url)))
+            }
+        }
+    }
+}
+
+/// Writes the absolute URL string.
+impl<R: ReferenceCounting> fmt::Display for Retained<NSURLRef, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let string = unsafe { NSStringRef::from_id(self.absoluteString()) };
+        fmt::Display::fmt(&*unsafe { string.to_str() }, f)
+    }
+}
+
+/// The error returned when parsing a string that isn't a valid URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseURLError {
+    _priv: (),
+}
+
+impl fmt::Display for ParseURLError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid URL string")
+    }
+}
+
+impl Error for ParseURLError {}
+
+/// The result of comparing two values, such as with `NSString::compare_`.
+#[cfg_attr(target_pointer_width = "32", repr(i32))]
+#[cfg_attr(target_pointer_width = "64", repr(i64))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NSComparisonResult {
+    NSOrderedAscending = -1,
+    NSOrderedSame = 0,
+    NSOrderedDescending = 1,
+}
+
+encode_as!(NSComparisonResult => NSInteger);
+
+impl From<NSComparisonResult> for Ordering {
+    fn from(result: NSComparisonResult) -> Ordering {
+        match result {
+            NSComparisonResult::NSOrderedAscending => Ordering::Less,
+            NSComparisonResult::NSOrderedSame => Ordering::Equal,
+            NSComparisonResult::NSOrderedDescending => Ordering::Greater,
+        }
+    }
+}
+
+/// A UTF-16 code unit.
+#[allow(non_camel_case_types)]
+pub type unichar = u16;
+
+object_ref! {
+    @no_debug
+    /// `Display`, `Debug` and comparison with `str` are implemented for
+    /// `Retained<NSStringRef>`, which keeps the string alive while it is read.
+    pub struct NSStringRef(NSString): NSObjectRef;
+}
+
+pub trait NSString {
+    unsafe fn alloc(_: Self) -> id {
+        msg_send![class!(NSString), alloc]
+    }
+
+    unsafe fn stringWithUTF8String_<'p>(_: Self, pool: &'p AutoreleasePool,
+                                        string: &CStr) -> Autoreleased<'p> {
+        pool.bind(msg_send![class!(NSString), stringWithUTF8String:string.as_ptr()])
+    }
+
+    unsafe fn stringByAppendingString_(self, other: id) -> id;
+    unsafe fn init_str(self, string: &str) -> Self;
+    unsafe fn init_utf16(self, characters: &[unichar]) -> Self;
+    unsafe fn UTF8String(self) -> *const libc::c_char;
+    /// The length of the string in UTF-8 bytes, unlike `length`, which counts UTF-16
+    /// code units.
+    unsafe fn len(self) -> usize;
+    unsafe fn isEqualToString(self, &str) -> bool;
+
+    /// The string's contents, borrowed from its UTF-8 representation for as long as this
+    /// reference to the string. The string must not be mutated or deallocated, and the
+    /// current autorelease pool must not be drained, while they are in use. Nil is empty.
+    unsafe fn to_str(&self) -> Cow<'_, str>;
+
+    // Getting Characters
+    unsafe fn length(self) -> NSUInteger;
+    unsafe fn characterAtIndex_(self, index: NSUInteger) -> unichar;
+    unsafe fn getCharacters_range_(self, buffer: *mut unichar, range: NSRange);
+    unsafe fn utf16(self) -> Vec<unichar>;
+
+    // Dividing Strings
+    unsafe fn substringFromIndex_(self, from: NSUInteger) -> id;
+    unsafe fn substringToIndex_(self, to: NSUInteger) -> id;
+    unsafe fn substringWithRange_(self, range: NSRange) -> id;
+
+    // Identifying and Comparing Strings
+    unsafe fn compare_(self, other: id) -> NSComparisonResult;
+    unsafe fn caseInsensitiveCompare_(self, other: id) -> NSComparisonResult;
+    unsafe fn hasPrefix_(self, prefix: id) -> BOOL;
+    unsafe fn hasSuffix_(self, suffix: id) -> BOOL;
+
+    // Changing Case
+    unsafe fn lowercaseString(self) -> id;
+    unsafe fn uppercaseString(self) -> id;
+    unsafe fn capitalizedString(self) -> id;
+}
+
+impl<T: IsKindOf<NSStringRef>> NSString for T {
+    unsafe fn isEqualToString(self, other: &str) -> bool {
+        let other = StrongPtr::new(NSString::alloc(nil).init_str(other));
+        let rv: BOOL = msg_send![self.as_id(), isEqualToString:*other];
+        rv != NO
+    }
+
+    unsafe fn stringByAppendingString_(self, other: id) -> id {
+        msg_send![self.as_id(), stringByAppendingString:other]
+    }
+
+    unsafe fn init_str(self, string: &str) -> T {
+        T::from_id(msg_send![self.as_id(),
+                             initWithBytes:string.as_ptr() as *const libc::c_void
+                                 length:string.len()
+                                 encoding:UTF8_ENCODING])
+    }
+
+    unsafe fn init_utf16(self, characters: &[unichar]) -> T {
+        T::from_id(msg_send![self.as_id(),
+                             initWithCharacters:characters.as_ptr() as *const libc::c_void
+                                         length:characters.len() as NSUInteger])
+    }
+
+    unsafe fn len(self) -> usize {
+        msg_send![self.as_id(), lengthOfBytesUsingEncoding:UTF8_ENCODING]
+    }
+
+    unsafe fn UTF8String(self) -> *const libc::c_char {
+        msg_send![self.as_id(), UTF8String]
+    }
+
+    unsafe fn to_str(&self) -> Cow<'_, str> {
+        if self.is_nil() {
+            return Cow::Borrowed("");
+        }
+        // Strings with unpaired surrogates have no UTF-8 representation, so
+        // `UTF8String` is null and `len` is zero.
+        let bytes = self.UTF8String() as *const u8;
+        let len = self.len();
+        if bytes.is_null() || (len == 0 && self.length() != 0) {
+            return Cow::Owned(String::from_utf16_lossy(&self.utf16()));
+        }
+        String::from_utf8_lossy(slice::from_raw_parts(bytes, len))
+    }
+
+    // Getting Characters
+
+    unsafe fn length(self) -> NSUInteger {
+        msg_send![self.as_id(), length]
+    }
+
+    unsafe fn characterAtIndex_(self, index: NSUInteger) -> unichar {
+        msg_send![self.as_id(), characterAtIndex:index]
+    }
+
+    unsafe fn getCharacters_range_(self, buffer: *mut unichar, range: NSRange) {
+        msg_send![self.as_id(), getCharacters:buffer as *mut libc::c_void range:range]
+    }
+
+    unsafe fn utf16(self) -> Vec<unichar> {
+        let length = self.length();
+        let mut characters = Vec::with_capacity(length as usize);
+        self.getCharacters_range_(characters.as_mut_ptr(), NSRange::new(0, length));
+        characters.set_len(length as usize);
+        characters
+    }
+
+    // Dividing Strings
+
+    unsafe fn substringFromIndex_(self, from: NSUInteger) -> id {
+        msg_send![self.as_id(), substringFromIndex:from]
+    }
+
+    unsafe fn substringToIndex_(self, to: NSUInteger) -> id {
+        msg_send![self.as_id(), substringToIndex:to]
+    }
+
+    unsafe fn substringWithRange_(self, range: NSRange) -> id {
+        msg_send![self.as_id(), substringWithRange:range]
+    }
+
+    // Identifying and Comparing Strings
+
+    unsafe fn compare_(self, other: id) -> NSComparisonResult {
+        msg_send![self.as_id(), compare:other]
+    }
+
+    unsafe fn caseInsensitiveCompare_(self, other: id) -> NSComparisonResult {
+        msg_send![self.as_id(), caseInsensitiveCompare:other]
+    }
+
+    unsafe fn hasPrefix_(self, prefix: id) -> BOOL {
+        msg_send![self.as_id(), hasPrefix:prefix]
+    }
+
+    unsafe fn hasSuffix_(self, suffix: id) -> BOOL {
+        msg_send![self.as_id(), hasSuffix:suffix]
+    }
+
+    // Changing Case
+
+    unsafe fn lowercaseString(self) -> id {
+        msg_send![self.as_id(), lowercaseString]
+    }
+
+    unsafe fn uppercaseString(self) -> id {
+        msg_send![self.as_id(), uppercaseString]
+    }
+
+    unsafe fn capitalizedString(self) -> id {
+        msg_send![self.as_id(), capitalizedString]
+    }
+}
+
+impl<'a> From<&'a str> for Retained<NSStringRef> {
+    fn from(string: &'a str) -> Retained<NSStringRef> {
+        unsafe { Retained::new(NSStringRef::from_id(NSString::alloc(nil)).init_str(string)) }
+    }
+}
+
+impl<'a, R: ReferenceCounting> From<&'a Retained<NSStringRef, R>> for String {
+    fn from(string: &'a Retained<NSStringRef, R>) -> String {
+        unsafe { string.to_str().into_owned() }
+    }
+}
+
+impl<R: ReferenceCounting> fmt::Display for Retained<NSStringRef, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&*unsafe { self.to_str() }, f)
+    }
+}
+
+impl<R: ReferenceCounting> fmt::Debug for Retained<NSStringRef, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_nil() {
+            f.write_str("nil")
+        } else {
+            fmt::Debug::fmt(&*unsafe { self.to_str() }, f)
+        }
+    }
+}
+
+impl<R: ReferenceCounting> PartialEq<str> for Retained<NSStringRef, R> {
+    fn eq(&self, other: &str) -> bool {
+        !self.is_nil() && unsafe { self.to_str() } == other
+    }
+}
+
+impl<'a, R: ReferenceCounting> PartialEq<&'a str> for Retained<NSStringRef, R> {
+    fn eq(&self, other: &&'a str) -> bool {
+        *self == **other
+    }
+}
+
+object_ref! {
+    pub struct NSDateRef(NSDate): NSObjectRef;
+}
+
+/// The seconds from the Unix epoch to the reference date, 1 January 2001 00:00:00 UTC,
+/// from which `NSDate` measures time.
+#[allow(non_upper_case_globals)]
+pub const NSTimeIntervalSince1970: NSTimeInterval = 978307200.0;
+
+fn reference_date() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(NSTimeIntervalSince1970 as u64)
+}
+
+/// Converts a duration to seconds, rounding durations an `f64` can't hold exactly.
+pub fn time_interval_from_duration(duration: Duration) -> NSTimeInterval {
+    duration.as_secs() as NSTimeInterval + duration.subsec_nanos() as NSTimeInterval / 1e9
+}
+
+/// Converts seconds to a duration, rounded to the nearest nanosecond, or returns `None`
+/// if `interval` is negative, NaN or too long.
+pub fn duration_from_time_interval(interval: NSTimeInterval) -> Option<Duration> {
+    // 2^64, the first whole number of seconds too long for a `Duration`.
+    if interval.is_nan() || interval < 0.0 || interval >= 18446744073709551616.0 {
+        return None;
+    }
+    let secs = interval.trunc();
+    let nanos = ((interval - secs) * 1e9).round() as u32;
+    // `nanos` may have rounded up to a whole second, which `Duration::new` carries.
+    Some(Duration::new(secs as u64, nanos))
+}
+
+/// The seconds from the reference date to `time`, which are negative for earlier times.
+pub fn time_interval_since_reference_date(time: SystemTime) -> NSTimeInterval {
+    match time.duration_since(reference_date()) {
+        Ok(after) => time_interval_from_duration(after),
+        Err(before) => -time_interval_from_duration(before.duration()),
+    }
+}
+
+/// The time `interval` seconds after the reference date, or `None` if `interval` is NaN
+/// or the time can't be represented by `SystemTime`.
+pub fn system_time_since_reference_date(interval: NSTimeInterval) -> Option<SystemTime> {
+    if interval >= 0.0 {
+        reference_date().checked_add(duration_from_time_interval(interval)?)
+    } else {
+        reference_date().checked_sub(duration_from_time_interval(-interval)?)
+    }
+}
+
+pub trait NSDate {
+    unsafe fn alloc(_: Self) -> id {
+        msg_send![class!(NSDate), alloc]
+    }
+
+    /// The current date and time.
+    unsafe fn date(_: Self) -> id {
+        msg_send![class!(NSDate), date]
+    }
+
+    unsafe fn dateWithTimeIntervalSinceNow_(_: Self, secs: NSTimeInterval) -> id {
+        msg_send![class!(NSDate), dateWithTimeIntervalSinceNow:secs]
+    }
+
+    unsafe fn dateWithTimeIntervalSinceReferenceDate_(_: Self, ti: NSTimeInterval) -> id {
+        msg_send![class!(NSDate), dateWithTimeIntervalSinceReferenceDate:ti]
+    }
+
+    unsafe fn dateWithTimeIntervalSince1970_(_: Self, secs: NSTimeInterval) -> id {
+        msg_send![class!(NSDate), dateWithTimeIntervalSince1970:secs]
+    }
+
+    unsafe fn distantPast(_: Self) -> id {
+        msg_send![class!(NSDate), distantPast]
+    }
+
+    unsafe fn distantFuture(_: Self) -> id {
+        msg_send![class!(NSDate), distantFuture]
+    }
+
+    unsafe fn initWithTimeIntervalSinceReferenceDate_(self, ti: NSTimeInterval) -> id;
+
+    // Getting Time Intervals
+    unsafe fn timeIntervalSinceReferenceDate(self) -> NSTimeInterval;
+    unsafe fn timeIntervalSince1970(self) -> NSTimeInterval;
+    /// Negative if the date is in the past.
+    unsafe fn timeIntervalSinceNow(self) -> NSTimeInterval;
+    unsafe fn timeIntervalSinceDate_(self, anotherDate: id) -> NSTimeInterval;
+    unsafe fn dateByAddingTimeInterval_(self, ti: NSTimeInterval) -> id;
+
+    // Comparing Dates
+    unsafe fn compare_(self, anotherDate: id) -> NSComparisonResult;
+    unsafe fn isEqualToDate_(self, anotherDate: id) -> BOOL;
+    unsafe fn earlierDate_(self, anotherDate: id) -> id;
+    unsafe fn laterDate_(self, anotherDate: id) -> id;
+
+    /// The date as a `SystemTime`, or `None` if it can't be represented as one.
+    unsafe fn to_system_time(self) -> Option<SystemTime>;
+}
+
+impl<T: IsKindOf<NSDateRef>> NSDate for T {
+    unsafe fn initWithTimeIntervalSinceReferenceDate_(self, ti: NSTimeInterval) -> id {
+        msg_send![self.as_id(), initWithTimeIntervalSinceReferenceDate:ti]
+    }
+
+    // Getting Time Intervals
+
+    unsafe fn timeIntervalSinceReferenceDate(self) -> NSTimeInterval {
+        msg_send![self.as_id(), timeIntervalSinceReferenceDate]
+    }
+
+    unsafe fn timeIntervalSince1970(self) -> NSTimeInterval {
+        msg_send![self.as_id(), timeIntervalSince1970]
+    }
+
+    unsafe fn timeIntervalSinceNow(self) -> NSTimeInterval {
+        msg_send![self.as_id(), timeIntervalSinceNow]
+    }
+
+    unsafe fn timeIntervalSinceDate_(self, anotherDate: id) -> NSTimeInterval {
+        msg_send![self.as_id(), timeIntervalSinceDate:anotherDate]
+    }
+
+    unsafe fn dateByAddingTimeInterval_(self, ti: NSTimeInterval) -> id {
+        msg_send![self.as_id(), dateByAddingTimeInterval:ti]
+    }
+
+    // Comparing Dates
+
+    unsafe fn compare_(self, anotherDate: id) -> NSComparisonResult {
+        msg_send![self.as_id(), compare:anotherDate]
+    }
+
+    unsafe fn isEqualToDate_(self, anotherDate: id) -> BOOL {
+        msg_send![self.as_id(), isEqualToDate:anotherDate]
+    }
+
+    unsafe fn earlierDate_(self, anotherDate: id) -> id {
+        msg_send![self.as_id(), earlierDate:anotherDate]
+    }
+
+    unsafe fn laterDate_(self, anotherDate: id) -> id {
+        msg_send![self.as_id(), laterDate:anotherDate]
+    }
+
+    unsafe fn to_system_time(self) -> Option<SystemTime> {
+        if self.is_nil() {
+            return None;
+        }
+        system_time_since_reference_date(self.timeIntervalSinceReferenceDate())
+    }
+}
+
+impl From<SystemTime> for Retained<NSDateRef> {
+    fn from(time: SystemTime) -> Retained<NSDateRef> {
+        let interval = time_interval_since_reference_date(time);
+        unsafe {
+            let date = NSDateRef::from_id(NSDate::alloc(nil))
+                .initWithTimeIntervalSinceReferenceDate_(interval);
+            Retained::new(NSDateRef::from_id(date))
+        }
+    }
+}
+
+object_ref! {
+    pub struct NSExceptionRef(NSException): NSObjectRef;
+}
+
+pub trait NSException {
+    unsafe fn name(self) -> id /* (NSString *) */;
+    unsafe fn reason(self) -> id /* (NSString *) */;
+    unsafe fn userInfo(self) -> id /* (NSDictionary *) */;
+}
+
+impl<T: IsKindOf<NSExceptionRef>> NSException for T {
+    unsafe fn name(self) -> id /* (NSString *) */ {
+        msg_send![self.as_id(), name]
+    }
+
+    unsafe fn reason(self) -> id /* (NSString *) */ {
+        msg_send![self.as_id(), reason]
+    }
+
+    unsafe fn userInfo(self) -> id /* (NSDictionary *) */ {
+        msg_send![self.as_id(), userInfo]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::file_url_path;
+
+    #[test]
+    pub fn test_file_url_path() {
+        assert_eq!(file_url_path(b"file:///tmp/a%20b/"), Some(b"/tmp/a b/".to_vec()));
+        assert_eq!(file_url_path(b"FILE://localhost/tmp/x"), Some(b"/tmp/x".to_vec()));
+        assert_eq!(file_url_path(b"file:///tmp/caf%C3%A9%FF"),
+                   Some(b"/tmp/caf\xc3\xa9\xff".to_vec()));
+        assert_eq!(file_url_path(b"file:///tmp/x?query#fragment"), Some(b"/tmp/x".to_vec()));
+        assert_eq!(file_url_path(b"file:///100%25%2"), Some(b"/100%%2".to_vec()));
+        assert_eq!(file_url_path(b"file:///%zz"), Some(b"/%zz".to_vec()));
+        assert_eq!(file_url_path(b"https://example.com/x"), None);
+        assert_eq!(file_url_path(b"file://"), None);
+    }
 }
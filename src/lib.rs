@@ -14,11 +14,28 @@
 
 #[macro_use]
 extern crate bitflags;
+extern crate block;
 extern crate libc;
+#[cfg(target_os = "macos")]
 extern crate core_graphics;
-#[macro_use]
+#[cfg(feature = "euclid")]
+extern crate euclid;
 extern crate objc;
+#[cfg(feature = "exception")]
+extern crate objc_exception;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
+#[macro_use]
+mod macros;
 
 pub mod appkit;
 pub mod base;
+pub mod coordinates;
+pub mod encoding;
+#[cfg(feature = "exception")]
+pub mod exception;
 pub mod foundation;
+pub mod object;
+pub mod rc;
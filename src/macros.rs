@@ -0,0 +1,147 @@
+// Copyright 2013 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// Returns the class with the given name, which is looked up the first time this call
+/// site runs and then cached. Panics if the class isn't loaded; `base::try_class` is the
+/// fallible, uncached lookup.
+#[macro_export]
+macro_rules! class {
+    ($name:ident) => ({
+        static CLASS: $crate::base::CachedClass = $crate::base::CachedClass::new();
+        CLASS.get(stringify!($name))
+    })
+}
+
+/// Returns the selector with the given name, like objc's `sel!`, but registers it only
+/// the first time this call site runs. The name is checked to be a valid selector when
+/// it's compiled: either one identifier, or identifiers each followed by a colon.
+#[macro_export]
+macro_rules! selector {
+    ($name:ident) => ({
+        static SEL: $crate::base::CachedSel = $crate::base::CachedSel::new();
+        SEL.get(concat!(stringify!($name), '\0'))
+    });
+    ($($name:ident :)+) => ({
+        static SEL: $crate::base::CachedSel = $crate::base::CachedSel::new();
+        SEL.get(concat!($(stringify!($name), ':'),+, '\0'))
+    });
+}
+
+/// Sends a message like `msg_send!`, inside `exception::catch_exception`, returning a
+/// `Result` with the exception it throws. See `exception::try_send`.
+#[cfg(feature = "exception")]
+#[macro_export]
+macro_rules! try_msg_send {
+    ($obj:expr, $name:ident) => ({
+        $crate::exception::try_send(&*$obj, selector!($name), ())
+    });
+    ($obj:expr, $($name:ident : $arg:expr)+) => ({
+        $crate::exception::try_send(&*$obj, selector!($($name:)+), ($($arg,)*))
+    });
+}
+
+/// Implements `objc::Encode` for C enums and bitflags, which are passed to and from
+/// Objective-C as the integer type they are represented by.
+macro_rules! encode_as {
+    ($($t:ty => $repr:ty),* $(,)*) => {
+        $(
+            unsafe impl ::objc::Encode for $t {
+                fn encode() -> ::objc::Encoding {
+                    <$repr as ::objc::Encode>::encode()
+                }
+            }
+        )*
+    }
+}
+
+/// objc's `msg_send!`, with each selector registered once by `selector!`.
+#[cfg(not(feature = "verify_message"))]
+macro_rules! msg_send {
+    ($obj:expr, $name:ident) => ({
+        #[inline(always)]
+        unsafe fn to_mut<T>(ptr: *const T) -> *mut T { ptr as *mut T }
+        ::objc::MessageArguments::send((), to_mut(&*$obj), selector!($name))
+    });
+    ($obj:expr, $($name:ident : $arg:expr)+) => ({
+        #[inline(always)]
+        unsafe fn to_mut<T>(ptr: *const T) -> *mut T { ptr as *mut T }
+        ::objc::MessageArguments::send(($($arg,)*), to_mut(&*$obj), selector!($($name:)+))
+    });
+}
+
+/// With the `verify_message` feature, checks every message the bindings send against
+/// the signature of the method it calls, using `encoding::verify_message`, and panics
+/// on a mismatch.
+#[cfg(feature = "verify_message")]
+macro_rules! msg_send {
+    ($obj:expr, $name:ident) => ({
+        $crate::encoding::send_verified(&*$obj, selector!($name), ())
+    });
+    ($obj:expr, $($name:ident : $arg:expr)+) => ({
+        $crate::encoding::send_verified(&*$obj, selector!($($name:)+), ($($arg,)*))
+    });
+}
+
+/// Declares a handle type for an Objective-C class, which is a kind of itself and of
+/// each of the listed superclass handles. See `object`.
+///
+/// The `@no_debug` form leaves out the `Debug` impl, for handles which print their
+/// contents instead of their address.
+macro_rules! object_ref {
+    ($(#[$attr:meta])* pub struct $name:ident($class:ident) $(: $($sup:ty),+)*;) => {
+        object_ref!(@no_debug $(#[$attr])* pub struct $name($class) $(: $($sup),+)*;);
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                write!(f, "{}({:p})", stringify!($name), self.0)
+            }
+        }
+    };
+    (@no_debug $(#[$attr:meta])* pub struct $name:ident($class:ident) $(: $($sup:ty),+)*;) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+        pub struct $name($crate::base::id);
+
+        unsafe impl $crate::object::AsId for $name {
+            #[inline]
+            unsafe fn from_id(obj: $crate::base::id) -> $name {
+                $name(obj)
+            }
+
+            #[inline]
+            fn as_id(self) -> $crate::base::id {
+                self.0
+            }
+        }
+
+        unsafe impl $crate::object::ObjCClass for $name {
+            #[inline]
+            fn class_name() -> &'static str {
+                stringify!($class)
+            }
+
+            #[inline]
+            fn class() -> $crate::base::Class {
+                class!($class)
+            }
+        }
+
+        unsafe impl $crate::object::IsKindOf<$name> for $name {}
+        $($(unsafe impl $crate::object::IsKindOf<$sup> for $name {})+)*
+
+        impl From<$name> for $crate::base::id {
+            #[inline]
+            fn from(obj: $name) -> $crate::base::id {
+                obj.0
+            }
+        }
+
+        encode_as!($name => $crate::base::id);
+    };
+}
@@ -0,0 +1,96 @@
+// Copyright 2013 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Typed handles to Objective-C objects.
+//!
+//! The `foundation` and `appkit` traits are implemented for `id`, which can be any
+//! object, and for a handle type per class, such as `appkit::NSWindowRef`. A handle
+//! only implements the traits of its class and superclasses, so sending `addItem_` to
+//! an `NSWindowRef` doesn't compile, where with an `id` it raises "unrecognized
+//! selector" at runtime.
+//!
+//! Handles convert to their superclasses with `upcast`, and back with `downcast`,
+//! which checks the class with `isKindOfClass:`.
+
+use base::{id, nil, Class, BOOL, NO};
+
+/// A pointer to an Objective-C object, which may be nil.
+pub unsafe trait AsId: Copy + 'static {
+    /// Wraps `obj` without checking its class.
+    unsafe fn from_id(obj: id) -> Self;
+
+    fn as_id(self) -> id;
+
+    #[inline]
+    fn is_nil(self) -> bool {
+        self.as_id().is_null()
+    }
+}
+
+unsafe impl AsId for id {
+    #[inline]
+    unsafe fn from_id(obj: id) -> id {
+        obj
+    }
+
+    #[inline]
+    fn as_id(self) -> id {
+        self
+    }
+}
+
+/// A handle to an instance of an Objective-C class or one of its subclasses, declared
+/// with `object_ref!`.
+pub unsafe trait ObjCClass: AsId {
+    /// The name of the class.
+    fn class_name() -> &'static str;
+
+    /// The class object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the class isn't loaded; `base::try_class(Self::class_name())` checks
+    /// for it instead.
+    fn class() -> Class;
+
+    /// Returns `obj` as this class if it is an instance of it or of a subclass, and
+    /// `None` if it isn't or is nil.
+    unsafe fn downcast<T: AsId>(obj: T) -> Option<Self> {
+        let obj = obj.as_id();
+        if obj.is_null() {
+            return None;
+        }
+        let is_kind: BOOL = msg_send![obj, isKindOfClass:Self::class()];
+        if is_kind != NO {
+            Some(Self::from_id(obj))
+        } else {
+            None
+        }
+    }
+
+    /// Returns this object as one of its superclasses.
+    #[inline]
+    fn upcast<T: ObjCClass>(self) -> T where Self: IsKindOf<T> {
+        unsafe { T::from_id(self.as_id()) }
+    }
+
+    /// A nil handle.
+    #[inline]
+    fn nil() -> Self {
+        unsafe { Self::from_id(nil) }
+    }
+}
+
+/// Every `Self` is an instance of `T`'s class or a subclass of it, so `Self` has all of
+/// `T`'s methods.
+///
+/// `id` is a kind of every class, since it can't be checked statically.
+pub unsafe trait IsKindOf<T: ObjCClass>: AsId {}
+
+unsafe impl<T: ObjCClass> IsKindOf<T> for id {}
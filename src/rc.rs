@@ -0,0 +1,503 @@
+// Copyright 2013 The Servo Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reference counted pointers to Objective-C objects, and autorelease pools.
+//!
+//! A `StrongPtr` owns one reference to an object: it retains on `Clone` and releases
+//! on `Drop`. It dereferences to `id`, so the `foundation` and `appkit` traits can be
+//! used through it directly. `Retained` is the same for a typed handle, such as
+//! `Retained<NSStringRef>`. A `WeakPtr` is an Objective-C weak reference, which becomes
+//! nil once the object is deallocated.
+//!
+//! `autoreleasepool` runs a closure inside an autorelease pool which is always
+//! drained, and `Autoreleased` references can't outlive the pool they are in. The
+//! convenience constructors of the `foundation` collections and strings, such as
+//! `NSArray::array`, return their objects as `Autoreleased` references in a pool.
+
+use base::{id, nil};
+use libc::c_void;
+use object::AsId;
+use objc::runtime::Object;
+use std::any::TypeId;
+use std::cell::{RefCell, UnsafeCell};
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Deref;
+
+/// The reference counting operations `StrongPtr`, `WeakPtr` and `AutoreleasePool` are
+/// built on.
+///
+/// `ObjcRuntime` implements them with the Objective-C runtime; other implementations
+/// are mostly useful for testing ownership without one. Each implementation has its
+/// own stack of autorelease pools, so only pools of the same implementation nest.
+pub trait ReferenceCounting: 'static {
+    unsafe fn retain(obj: id) -> id;
+    unsafe fn release(obj: id);
+    unsafe fn autorelease(obj: id) -> id;
+    /// Stores a weak reference to `obj` in `location`, which must stay at the same
+    /// address until it is passed to `destroy_weak`.
+    unsafe fn init_weak(location: *mut id, obj: id) -> id;
+    /// Returns the object `location` refers to, retained, or nil if it was deallocated.
+    unsafe fn load_weak_retained(location: *mut id) -> id;
+    unsafe fn copy_weak(to: *mut id, from: *mut id);
+    unsafe fn destroy_weak(location: *mut id);
+    /// Pushes a new autorelease pool, returning the token to pop it with.
+    unsafe fn autorelease_pool_push() -> *mut c_void;
+    /// Drains the pool pushed with `pool`, which must be the innermost one.
+    unsafe fn autorelease_pool_pop(pool: *mut c_void);
+}
+
+/// The Objective-C runtime's reference counting.
+pub enum ObjcRuntime {}
+
+extern {
+    fn objc_retain(obj: id) -> id;
+    fn objc_release(obj: id);
+    fn objc_autorelease(obj: id) -> id;
+    fn objc_initWeak(location: *mut id, obj: id) -> id;
+    fn objc_loadWeakRetained(location: *mut id) -> id;
+    fn objc_copyWeak(to: *mut id, from: *mut id);
+    fn objc_destroyWeak(location: *mut id);
+    fn objc_autoreleasePoolPush() -> *mut c_void;
+    fn objc_autoreleasePoolPop(pool: *mut c_void);
+}
+
+impl ReferenceCounting for ObjcRuntime {
+    #[inline]
+    unsafe fn retain(obj: id) -> id {
+        objc_retain(obj)
+    }
+
+    #[inline]
+    unsafe fn release(obj: id) {
+        objc_release(obj)
+    }
+
+    #[inline]
+    unsafe fn autorelease(obj: id) -> id {
+        objc_autorelease(obj)
+    }
+
+    #[inline]
+    unsafe fn init_weak(location: *mut id, obj: id) -> id {
+        objc_initWeak(location, obj)
+    }
+
+    #[inline]
+    unsafe fn load_weak_retained(location: *mut id) -> id {
+        objc_loadWeakRetained(location)
+    }
+
+    #[inline]
+    unsafe fn copy_weak(to: *mut id, from: *mut id) {
+        objc_copyWeak(to, from)
+    }
+
+    #[inline]
+    unsafe fn destroy_weak(location: *mut id) {
+        objc_destroyWeak(location)
+    }
+
+    #[inline]
+    unsafe fn autorelease_pool_push() -> *mut c_void {
+        objc_autoreleasePoolPush()
+    }
+
+    #[inline]
+    unsafe fn autorelease_pool_pop(pool: *mut c_void) {
+        objc_autoreleasePoolPop(pool)
+    }
+}
+
+/// A strong reference to an object, which may be nil.
+pub struct StrongPtr<R: ReferenceCounting = ObjcRuntime> {
+    ptr: id,
+    rc: PhantomData<R>,
+}
+
+impl StrongPtr {
+    /// Takes ownership of a +1 reference, such as the result of `alloc`, `new`, `copy`
+    /// or `mutableCopy`. It is released when the `StrongPtr` is dropped.
+    #[inline]
+    pub unsafe fn new(ptr: id) -> StrongPtr {
+        StrongPtr::from_owned(ptr)
+    }
+
+    /// Retains a +0 reference, such as the result of most other methods, which the
+    /// caller doesn't own.
+    #[inline]
+    pub unsafe fn retain(ptr: id) -> StrongPtr {
+        StrongPtr::from_unowned(ptr)
+    }
+}
+
+impl<R: ReferenceCounting> StrongPtr<R> {
+    /// Like `StrongPtr::new`, for any `ReferenceCounting` implementation.
+    #[inline]
+    pub unsafe fn from_owned(ptr: id) -> StrongPtr<R> {
+        StrongPtr {
+            ptr: ptr,
+            rc: PhantomData,
+        }
+    }
+
+    /// Like `StrongPtr::retain`, for any `ReferenceCounting` implementation.
+    #[inline]
+    pub unsafe fn from_unowned(ptr: id) -> StrongPtr<R> {
+        StrongPtr::from_owned(R::retain(ptr))
+    }
+
+    /// Creates a weak reference to the object.
+    #[inline]
+    pub fn weak(&self) -> WeakPtr<R> {
+        unsafe { WeakPtr::init(self.ptr) }
+    }
+
+    /// Gives up ownership without releasing, returning the +1 reference.
+    #[inline]
+    pub fn into_raw(self) -> id {
+        let ptr = self.ptr;
+        mem::forget(self);
+        ptr
+    }
+
+    /// Hands the reference to the current autorelease pool, returning it as a +0
+    /// reference that stays valid until the pool is drained.
+    #[inline]
+    pub fn autorelease(self) -> id {
+        unsafe { R::autorelease(self.into_raw()) }
+    }
+}
+
+impl<R: ReferenceCounting> Clone for StrongPtr<R> {
+    #[inline]
+    fn clone(&self) -> StrongPtr<R> {
+        unsafe { StrongPtr::from_unowned(self.ptr) }
+    }
+}
+
+impl<R: ReferenceCounting> Drop for StrongPtr<R> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { R::release(self.ptr) }
+    }
+}
+
+impl<R: ReferenceCounting> Deref for StrongPtr<R> {
+    type Target = id;
+
+    #[inline]
+    fn deref(&self) -> &id {
+        &self.ptr
+    }
+}
+
+impl<R: ReferenceCounting> fmt::Debug for StrongPtr<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StrongPtr({:p})", self.ptr)
+    }
+}
+
+/// A strong reference to an object through one of its typed handles, such as
+/// `Retained<NSStringRef>`. It dereferences to the handle, and otherwise behaves like a
+/// `StrongPtr`.
+pub struct Retained<T: AsId, R: ReferenceCounting = ObjcRuntime> {
+    obj: T,
+    rc: PhantomData<R>,
+}
+
+impl<T: AsId> Retained<T> {
+    /// Takes ownership of a +1 reference, like `StrongPtr::new`.
+    #[inline]
+    pub unsafe fn new(obj: T) -> Retained<T> {
+        Retained::from_owned(obj)
+    }
+
+    /// Retains a +0 reference, like `StrongPtr::retain`.
+    #[inline]
+    pub unsafe fn retain(obj: T) -> Retained<T> {
+        Retained::from_unowned(obj)
+    }
+}
+
+impl<T: AsId, R: ReferenceCounting> Retained<T, R> {
+    /// Like `Retained::new`, for any `ReferenceCounting` implementation.
+    #[inline]
+    pub unsafe fn from_owned(obj: T) -> Retained<T, R> {
+        Retained {
+            obj: obj,
+            rc: PhantomData,
+        }
+    }
+
+    /// Like `Retained::retain`, for any `ReferenceCounting` implementation.
+    #[inline]
+    pub unsafe fn from_unowned(obj: T) -> Retained<T, R> {
+        R::retain(obj.as_id());
+        Retained::from_owned(obj)
+    }
+
+    /// Gives up ownership without releasing, returning the +1 reference.
+    #[inline]
+    pub fn into_raw(self) -> T {
+        let obj = self.obj;
+        mem::forget(self);
+        obj
+    }
+
+    /// Hands the reference to the current autorelease pool, like
+    /// `StrongPtr::autorelease`.
+    #[inline]
+    pub fn autorelease(self) -> T {
+        let obj = self.into_raw();
+        unsafe { R::autorelease(obj.as_id()) };
+        obj
+    }
+}
+
+impl<T: AsId, R: ReferenceCounting> Clone for Retained<T, R> {
+    #[inline]
+    fn clone(&self) -> Retained<T, R> {
+        unsafe { Retained::from_unowned(self.obj) }
+    }
+}
+
+impl<T: AsId, R: ReferenceCounting> Drop for Retained<T, R> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { R::release(self.obj.as_id()) }
+    }
+}
+
+impl<T: AsId, R: ReferenceCounting> Deref for Retained<T, R> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.obj
+    }
+}
+
+impl<T: AsId, R: ReferenceCounting> From<Retained<T, R>> for StrongPtr<R> {
+    #[inline]
+    fn from(obj: Retained<T, R>) -> StrongPtr<R> {
+        unsafe { StrongPtr::from_owned(obj.into_raw().as_id()) }
+    }
+}
+
+impl<T: AsId + fmt::Debug, R: ReferenceCounting> fmt::Debug for Retained<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.obj.fmt(f)
+    }
+}
+
+impl<T: AsId + fmt::Display, R: ReferenceCounting> fmt::Display for Retained<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.obj.fmt(f)
+    }
+}
+
+impl<T, U: ?Sized, R> PartialEq<U> for Retained<T, R>
+        where T: AsId + PartialEq<U>, R: ReferenceCounting {
+    #[inline]
+    fn eq(&self, other: &U) -> bool {
+        self.obj == *other
+    }
+}
+
+/// A weak reference to an object, which becomes nil once the object is deallocated.
+pub struct WeakPtr<R: ReferenceCounting = ObjcRuntime> {
+    // The runtime tracks weak references by address, so the location is boxed to
+    // keep it in place when the `WeakPtr` moves.
+    location: Box<UnsafeCell<id>>,
+    rc: PhantomData<R>,
+}
+
+impl WeakPtr {
+    /// Creates a weak reference to `obj`, which may be nil.
+    #[inline]
+    pub unsafe fn new(obj: id) -> WeakPtr {
+        WeakPtr::init(obj)
+    }
+}
+
+impl<R: ReferenceCounting> WeakPtr<R> {
+    unsafe fn init(obj: id) -> WeakPtr<R> {
+        let weak = WeakPtr::<R>::empty();
+        R::init_weak(weak.location.get(), obj);
+        weak
+    }
+
+    fn empty() -> WeakPtr<R> {
+        WeakPtr {
+            location: Box::new(UnsafeCell::new(nil)),
+            rc: PhantomData,
+        }
+    }
+
+    /// Returns a strong reference to the object, which is nil if it was deallocated.
+    #[inline]
+    pub fn load(&self) -> StrongPtr<R> {
+        unsafe { StrongPtr::from_owned(R::load_weak_retained(self.location.get())) }
+    }
+}
+
+impl<R: ReferenceCounting> Clone for WeakPtr<R> {
+    fn clone(&self) -> WeakPtr<R> {
+        let weak = WeakPtr::<R>::empty();
+        unsafe { R::copy_weak(weak.location.get(), self.location.get()) }
+        weak
+    }
+}
+
+impl<R: ReferenceCounting> Drop for WeakPtr<R> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { R::destroy_weak(self.location.get()) }
+    }
+}
+
+impl<R: ReferenceCounting> fmt::Debug for WeakPtr<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WeakPtr({:p})", self.location.get())
+    }
+}
+
+/// Runs `f` inside a new autorelease pool, which is drained when `f` returns or panics.
+///
+/// Pools nest, and objects are always autoreleased into the innermost one, so only
+/// the innermost pool accepts new objects.
+pub fn autoreleasepool<T, F>(f: F) -> T where F: FnOnce(&AutoreleasePool) -> T {
+    AutoreleasePool::with(f)
+}
+
+// The number of pools open on this thread for each `ReferenceCounting` implementation,
+// which keep separate pool stacks.
+thread_local!(static POOL_DEPTHS: RefCell<Vec<(TypeId, usize)>> = const { RefCell::new(Vec::new()) });
+
+fn with_pool_depth<R: ReferenceCounting, T, F>(f: F) -> T where F: FnOnce(&mut usize) -> T {
+    POOL_DEPTHS.with(|depths| {
+        let mut depths = depths.borrow_mut();
+        let rc = TypeId::of::<R>();
+        let index = match depths.iter().position(|&(id, _)| id == rc) {
+            Some(index) => index,
+            None => {
+                depths.push((rc, 0));
+                depths.len() - 1
+            }
+        };
+        f(&mut depths[index].1)
+    })
+}
+
+/// An autorelease pool, created by `autoreleasepool`.
+pub struct AutoreleasePool<R: ReferenceCounting = ObjcRuntime> {
+    context: *mut c_void,
+    depth: usize,
+    rc: PhantomData<R>,
+}
+
+impl<R: ReferenceCounting> AutoreleasePool<R> {
+    /// Runs `f` inside a new autorelease pool, like `autoreleasepool`.
+    pub fn with<T, F>(f: F) -> T where F: FnOnce(&AutoreleasePool<R>) -> T {
+        let depth = with_pool_depth::<R, _, _>(|depth| {
+            *depth += 1;
+            *depth
+        });
+        let pool = AutoreleasePool {
+            context: unsafe { R::autorelease_pool_push() },
+            depth: depth,
+            rc: PhantomData,
+        };
+        f(&pool)
+    }
+
+    fn assert_innermost(&self) {
+        let innermost = with_pool_depth::<R, _, _>(|depth| *depth == self.depth);
+        assert!(innermost, "objects can only be autoreleased into the innermost pool");
+    }
+
+    /// Autoreleases `ptr` into this pool, returning a reference that is valid as long
+    /// as the pool is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not the innermost pool.
+    pub fn autorelease<'p>(&'p self, ptr: StrongPtr<R>) -> Autoreleased<'p, R> {
+        self.assert_innermost();
+        unsafe { self.bind(ptr.autorelease()) }
+    }
+
+    /// Binds an object that is in this pool, such as the result of a convenience
+    /// constructor like `+[NSString stringWithUTF8String:]`, to the pool's lifetime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not the innermost pool.
+    pub unsafe fn bind<'p>(&'p self, obj: id) -> Autoreleased<'p, R> {
+        self.assert_innermost();
+        Autoreleased {
+            ptr: obj,
+            pool: PhantomData,
+        }
+    }
+}
+
+impl<R: ReferenceCounting> Drop for AutoreleasePool<R> {
+    fn drop(&mut self) {
+        unsafe { R::autorelease_pool_pop(self.context) }
+        with_pool_depth::<R, _, _>(|depth| *depth = self.depth - 1);
+    }
+}
+
+impl<R: ReferenceCounting> fmt::Debug for AutoreleasePool<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AutoreleasePool({:p})", self.context)
+    }
+}
+
+/// An autoreleased object, which is valid as long as the pool `'p` it is in.
+///
+/// Unlike `StrongPtr` it doesn't dereference to `id`, which could be copied out of
+/// the pool; the object is reached through `get`, or kept with `retain`.
+pub struct Autoreleased<'p, R: ReferenceCounting + 'p = ObjcRuntime> {
+    ptr: id,
+    pool: PhantomData<&'p AutoreleasePool<R>>,
+}
+
+impl<'p, R: ReferenceCounting> Autoreleased<'p, R> {
+    /// Returns the object, borrowed for as long as the pool it is in, or `None` if it
+    /// is nil.
+    #[inline]
+    pub fn get(&self) -> Option<&'p Object> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// Retains the object, so that it can be used after the pool is drained.
+    #[inline]
+    pub fn retain(&self) -> StrongPtr<R> {
+        unsafe { StrongPtr::from_unowned(self.ptr) }
+    }
+}
+
+impl<'p, R: ReferenceCounting> Clone for Autoreleased<'p, R> {
+    #[inline]
+    fn clone(&self) -> Autoreleased<'p, R> {
+        *self
+    }
+}
+
+impl<'p, R: ReferenceCounting> Copy for Autoreleased<'p, R> {}
+
+impl<'p, R: ReferenceCounting> fmt::Debug for Autoreleased<'p, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Autoreleased({:p})", self.ptr)
+    }
+}
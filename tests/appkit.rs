@@ -0,0 +1,127 @@
+extern crate cocoa;
+extern crate objc;
+
+#[cfg(test)]
+mod appkit {
+    mod backing_alignment {
+        use cocoa::appkit::*;
+        use cocoa::foundation::{NSPoint, NSSize, NSRect};
+
+        fn rect(x: CGFloat, y: CGFloat, width: CGFloat, height: CGFloat) -> NSRect {
+            NSRect::new(NSPoint::new(x, y), NSSize::new(width, height))
+        }
+
+        #[test]
+        fn test_all_edges_outward() {
+            let aligned = backing_aligned_rect(rect(0.3, 0.3, 10.2, 10.2), 1., NSAlignAllEdgesOutward);
+            assert_eq!(aligned, Some(rect(0., 0., 11., 11.)));
+        }
+
+        #[test]
+        fn test_all_edges_inward() {
+            let aligned = backing_aligned_rect(rect(0.3, 0.3, 10.2, 10.2), 1., NSAlignAllEdgesInward);
+            assert_eq!(aligned, Some(rect(1., 1., 9., 9.)));
+        }
+
+        #[test]
+        fn test_all_edges_nearest() {
+            let aligned = backing_aligned_rect(rect(0.3, 0.7, 10.2, 10.2), 1., NSAlignAllEdgesNearest);
+            assert_eq!(aligned, Some(rect(0., 1., 11., 10.)));
+        }
+
+        #[test]
+        fn test_retina_scale() {
+            let aligned = backing_aligned_rect(rect(0.3, 0.3, 10.2, 10.2), 2., NSAlignAllEdgesOutward);
+            assert_eq!(aligned, Some(rect(0., 0., 10.5, 10.5)));
+
+            let aligned = backing_aligned_rect(rect(0.75, 1.25, 3., 3.), 2., NSAlignAllEdgesNearest);
+            assert_eq!(aligned, Some(rect(1., 1.5, 3., 3.)));
+        }
+
+        #[test]
+        fn test_edge_and_size() {
+            let options = NSAlignMinXOutward | NSAlignWidthInward |
+                          NSAlignMaxYInward | NSAlignHeightOutward;
+            let aligned = backing_aligned_rect(rect(0.5, 0.5, 10.5, 10.5), 1., options);
+            assert_eq!(aligned, Some(rect(0., 0., 10., 11.)));
+        }
+
+        #[test]
+        fn test_flipped_ties() {
+            let unflipped = backing_aligned_rect(rect(0.5, 0.5, 2., 2.), 1., NSAlignAllEdgesNearest);
+            assert_eq!(unflipped, Some(rect(1., 1., 2., 2.)));
+
+            let flipped = backing_aligned_rect(rect(0.5, 0.5, 2., 2.), 1.,
+                                               NSAlignAllEdgesNearest | NSAlignRectFlipped);
+            assert_eq!(flipped, Some(rect(1., 0., 2., 2.)));
+        }
+
+        #[test]
+        fn test_too_few_options() {
+            let options = NSAlignMinXInward | NSAlignMinYInward | NSAlignMaxYInward;
+            assert_eq!(backing_aligned_rect(rect(0., 0., 1., 1.), 1., options), None);
+        }
+
+        #[test]
+        fn test_conflicting_options() {
+            let options = NSAlignAllEdgesInward | NSAlignMinXOutward;
+            assert_eq!(backing_aligned_rect(rect(0., 0., 1., 1.), 1., options), None);
+        }
+
+        #[test]
+        fn test_invalid_scale_factor() {
+            for &scale_factor in &[0., -1., ::std::f64::NAN, ::std::f64::INFINITY] {
+                let scale_factor = scale_factor as CGFloat;
+                let aligned = backing_aligned_rect(rect(0., 0., 1., 1.), scale_factor,
+                                                   NSAlignAllEdgesNearest);
+                assert_eq!(aligned, None);
+            }
+        }
+    }
+    mod encoding {
+        use cocoa::appkit::*;
+        use objc::{Encode, Encoding};
+
+        #[cfg(target_pointer_width = "64")]
+        const NSINTEGER: &'static str = "q";
+        #[cfg(target_pointer_width = "64")]
+        const NSUINTEGER: &'static str = "Q";
+        #[cfg(target_pointer_width = "32")]
+        const NSINTEGER: &'static str = "i";
+        #[cfg(target_pointer_width = "32")]
+        const NSUINTEGER: &'static str = "I";
+
+        #[test]
+        fn test_encoding() {
+            let table: Vec<(&'static str, Encoding, &'static str)> = vec![
+                ("NSApplicationActivationPolicy", NSApplicationActivationPolicy::encode(), NSINTEGER),
+                ("NSApplicationActivationOptions", NSApplicationActivationOptions::encode(), NSUINTEGER),
+                ("NSApplicationTerminateReply", NSApplicationTerminateReply::encode(), NSUINTEGER),
+                ("NSWindowMask", NSWindowMask::encode(), NSUINTEGER),
+                ("NSWindowTitleVisibility", NSWindowTitleVisibility::encode(), NSINTEGER),
+                ("NSBackingStoreType", NSBackingStoreType::encode(), NSUINTEGER),
+                ("NSWindowOrderingMode", NSWindowOrderingMode::encode(), NSINTEGER),
+                ("NSAlignmentOptions", NSAlignmentOptions::encode(), "Q"),
+                ("NSOpenGLPixelFormatAttribute", NSOpenGLPixelFormatAttribute::encode(), "I"),
+                ("NSOpenGLPFAOpenGLProfiles", NSOpenGLPFAOpenGLProfiles::encode(), "Q"),
+                ("NSOpenGLContextParameter", NSOpenGLContextParameter::encode(), NSINTEGER),
+                ("NSWindowButton", NSWindowButton::encode(), NSUINTEGER),
+                ("NSWindowCollectionBehavior", NSWindowCollectionBehavior::encode(), NSUINTEGER),
+                ("NSWindowOcclusionState", NSWindowOcclusionState::encode(), NSUINTEGER),
+                ("NSEventSwipeTrackingOptions", NSEventSwipeTrackingOptions::encode(), NSUINTEGER),
+                ("NSEventGestureAxis", NSEventGestureAxis::encode(), NSINTEGER),
+                ("NSEventPhase", NSEventPhase::encode(), NSUINTEGER),
+                ("NSTouchPhase", NSTouchPhase::encode(), NSUINTEGER),
+                ("NSEventType", NSEventType::encode(), NSUINTEGER),
+                ("NSEventMask", NSEventMask::encode(), "Q"),
+                ("NSEventModifierFlags", NSEventModifierFlags::encode(), NSUINTEGER),
+                ("NSPointingDeviceType", NSPointingDeviceType::encode(), NSUINTEGER),
+                ("NSEventButtonMask", NSEventButtonMask::encode(), NSUINTEGER),
+                ("NSEventSubtype", NSEventSubtype::encode(), "s"),
+            ];
+            for &(name, ref encoding, expected) in &table {
+                assert_eq!((name, encoding.as_str()), (name, expected));
+            }
+        }
+    }
+}
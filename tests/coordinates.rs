@@ -0,0 +1,90 @@
+extern crate cocoa;
+
+#[cfg(test)]
+mod coordinates {
+    use cocoa::coordinates::*;
+    use cocoa::foundation::{CGFloat, NSPoint, NSSize, NSRect};
+
+    fn rect(x: CGFloat, y: CGFloat, width: CGFloat, height: CGFloat) -> NSRect {
+        NSRect::new(NSPoint::new(x, y), NSSize::new(width, height))
+    }
+
+    // A 1440x900 primary screen with a 1920x1080 screen to its right, aligned at the
+    // bottom, and a 1280x1024 screen above it.
+    fn layout() -> ScreenLayout {
+        ScreenLayout::new(vec![rect(0., 0., 1440., 900.),
+                               rect(1440., 0., 1920., 1080.),
+                               rect(0., 900., 1280., 1024.)]).unwrap()
+    }
+
+    #[test]
+    fn test_flip_point() {
+        assert_eq!(flip_point(NSPoint::new(10., 0.), 900.), NSPoint::new(10., 900.));
+        assert_eq!(flip_point(NSPoint::new(10., 900.), 900.), NSPoint::new(10., 0.));
+        assert_eq!(flip_point(flip_point(NSPoint::new(3., 4.), 900.), 900.), NSPoint::new(3., 4.));
+    }
+
+    #[test]
+    fn test_flip_rect() {
+        assert_eq!(flip_rect(rect(100., 0., 200., 100.), 900.), rect(100., 800., 200., 100.));
+        assert_eq!(flip_rect(rect(0., 0., 1440., 900.), 900.), rect(0., 0., 1440., 900.));
+        let r = rect(12., 34., 56., 78.);
+        assert_eq!(flip_rect(flip_rect(r, 900.), 900.), r);
+    }
+
+    #[test]
+    fn test_window_to_screen() {
+        let frame = rect(100., 200., 640., 480.);
+        assert_eq!(window_to_screen_point(NSPoint::new(10., 20.), frame), NSPoint::new(110., 220.));
+        assert_eq!(screen_to_window_point(NSPoint::new(110., 220.), frame), NSPoint::new(10., 20.));
+        assert_eq!(window_to_screen_rect(rect(0., 0., 5., 5.), frame), rect(100., 200., 5., 5.));
+        assert_eq!(screen_to_window_rect(rect(100., 200., 5., 5.), frame), rect(0., 0., 5., 5.));
+    }
+
+    #[test]
+    fn test_flipped_screens() {
+        let flipped = layout().flipped_screens();
+        assert_eq!(flipped[0], rect(0., 0., 1440., 900.));
+        assert_eq!(flipped[1], rect(1440., -180., 1920., 1080.));
+        assert_eq!(flipped[2], rect(0., -1024., 1280., 1024.));
+    }
+
+    #[test]
+    fn test_window_to_flipped() {
+        let layout = layout();
+        // A window on the upper screen, 100 points below its top edge.
+        let frame = rect(50., 1924. - 100. - 300., 400., 300.);
+        assert_eq!(layout.window_to_flipped_point(NSPoint::new(0., 300.), frame),
+                   NSPoint::new(50., -924.));
+        assert_eq!(layout.flipped_to_window_point(NSPoint::new(50., -924.), frame),
+                   NSPoint::new(0., 300.));
+        assert_eq!(layout.window_to_flipped_rect(rect(0., 0., 400., 300.), frame),
+                   rect(50., -924., 400., 300.));
+        assert_eq!(layout.flipped_to_window_rect(rect(50., -924., 400., 300.), frame),
+                   rect(0., 0., 400., 300.));
+    }
+
+    #[test]
+    fn test_screen_at_point() {
+        let layout = layout();
+        assert_eq!(layout.screen_at_point(NSPoint::new(10., 10.)), Some(0));
+        assert_eq!(layout.screen_at_point(NSPoint::new(2000., 1000.)), Some(1));
+        assert_eq!(layout.screen_at_point(NSPoint::new(10., 1000.)), Some(2));
+        assert_eq!(layout.screen_at_point(NSPoint::new(10., 900.)), Some(0));
+        assert_eq!(layout.screen_at_point(NSPoint::new(2000., 1500.)), None);
+    }
+
+    #[test]
+    fn test_screen_for_rect() {
+        let layout = layout();
+        assert_eq!(layout.screen_for_rect(rect(1300., 100., 400., 300.)), Some(1));
+        assert_eq!(layout.screen_for_rect(rect(1200., 100., 400., 300.)), Some(0));
+        assert_eq!(layout.screen_for_rect(rect(100., 800., 200., 300.)), Some(2));
+        assert_eq!(layout.screen_for_rect(rect(-500., -500., 10., 10.)), None);
+    }
+
+    #[test]
+    fn test_empty_layout() {
+        assert_eq!(ScreenLayout::new(vec![]), None);
+    }
+}
@@ -0,0 +1,239 @@
+extern crate cocoa;
+extern crate objc;
+
+#[cfg(test)]
+mod encoding {
+    use cocoa::base::{id, BOOL, SEL};
+    use cocoa::encoding::*;
+    use cocoa::foundation::{NSPoint, NSSize, NSRect, NSRange, NSUInteger};
+    use objc::Encode;
+
+    fn parse(s: &str) -> Encoding {
+        s.parse().unwrap()
+    }
+
+    fn signature(s: &str) -> MethodSignature {
+        s.parse().unwrap()
+    }
+
+    fn boxed(encoding: Encoding) -> Box<Encoding> {
+        Box::new(encoding)
+    }
+
+    #[test]
+    fn test_scalars() {
+        let table = [
+            ("c", Encoding::Char), ("s", Encoding::Short), ("i", Encoding::Int),
+            ("l", Encoding::Long), ("q", Encoding::LongLong), ("C", Encoding::UChar),
+            ("S", Encoding::UShort), ("I", Encoding::UInt), ("L", Encoding::ULong),
+            ("Q", Encoding::ULongLong), ("f", Encoding::Float), ("d", Encoding::Double),
+            ("D", Encoding::LongDouble), ("B", Encoding::Bool), ("v", Encoding::Void),
+            ("*", Encoding::String), ("@", Encoding::Object(None)), ("@?", Encoding::Block),
+            ("#", Encoding::Class), (":", Encoding::Sel), ("?", Encoding::Unknown),
+        ];
+        for &(s, ref expected) in &table {
+            assert_eq!(&parse(s), expected);
+            assert_eq!(expected.to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_structs() {
+        let point = Encoding::Struct("CGPoint".to_string(), vec![Encoding::Double, Encoding::Double]);
+        let size = Encoding::Struct("CGSize".to_string(), vec![Encoding::Double, Encoding::Double]);
+        let rect = Encoding::Struct("CGRect".to_string(), vec![point, size]);
+        assert_eq!(parse("{CGRect={CGPoint=dd}{CGSize=dd}}"), rect);
+
+        assert_eq!(parse("^{CGRect}"),
+                   Encoding::Pointer(boxed(Encoding::Struct("CGRect".to_string(), vec![]))));
+        assert_eq!(parse("(?=iQ)"),
+                   Encoding::Union("?".to_string(), vec![Encoding::Int, Encoding::ULongLong]));
+        assert_eq!(parse("[4{?=b3b5}]"),
+                   Encoding::Array(4, boxed(Encoding::Struct("?".to_string(),
+                                                             vec![Encoding::BitField(3),
+                                                                  Encoding::BitField(5)]))));
+    }
+
+    #[test]
+    fn test_named_fields() {
+        let point = Encoding::Struct("CGPoint".to_string(), vec![Encoding::Double, Encoding::Double]);
+        assert_eq!(parse("{CGPoint=\"x\"d\"y\"d}"), point);
+        assert_eq!(parse("^{CGRect=\"origin\"{CGPoint=\"x\"d\"y\"d}\"size\"{CGSize=\"width\"d\"height\"d}}"),
+                   parse("^{CGRect={CGPoint=dd}{CGSize=dd}}"));
+        assert_eq!(parse("(?=\"i\"i\"q\"Q)"),
+                   Encoding::Union("?".to_string(), vec![Encoding::Int, Encoding::ULongLong]));
+
+        // A quoted string after `@` is a class name only if another field or the end of
+        // the struct follows it.
+        let string = Encoding::Object(Some("NSString".to_string()));
+        assert_eq!(parse("{?=\"a\"@\"NSString\"\"b\"@\"NSString\"}"),
+                   Encoding::Struct("?".to_string(), vec![string.clone(), string.clone()]));
+        assert_eq!(parse("{?=\"a\"@\"b\"i}"),
+                   Encoding::Struct("?".to_string(), vec![Encoding::Object(None), Encoding::Int]));
+        assert_eq!(parse("{?=\"a\"@\"b\"@}"),
+                   Encoding::Struct("?".to_string(), vec![Encoding::Object(None),
+                                                          Encoding::Object(None)]));
+        assert_eq!(parse("{?=\"a\"{?=@\"NSString\"}}"),
+                   Encoding::Struct("?".to_string(),
+                                    vec![Encoding::Struct("?".to_string(), vec![string])]));
+    }
+
+    #[test]
+    fn test_block_signatures() {
+        assert_eq!(parse("@?<v@?>"), Encoding::Block);
+        assert_eq!(parse("@?<v@?@\"NSData\"Q>"), Encoding::Block);
+        assert_eq!(parse("@?<v@?@?<v@?{CGPoint=dd}>>"), Encoding::Block);
+        assert_eq!(parse("{?=\"handler\"@?<v@?>\"count\"Q}"),
+                   Encoding::Struct("?".to_string(), vec![Encoding::Block, Encoding::ULongLong]));
+
+        let signature: MethodSignature = "v32@0:8@?<v@?^vQ>16Q24".parse().unwrap();
+        assert_eq!(signature.arguments[2], Argument { encoding: Encoding::Block, offset: Some(16) });
+        assert_eq!(signature.arguments[3], Argument { encoding: Encoding::ULongLong, offset: Some(24) });
+    }
+
+    #[test]
+    fn test_pointers_and_qualifiers() {
+        assert_eq!(parse("^^v"), Encoding::Pointer(boxed(Encoding::Pointer(boxed(Encoding::Void)))));
+        assert_eq!(parse("^?"), Encoding::Pointer(boxed(Encoding::Unknown)));
+        assert_eq!(parse("r*"), Encoding::Qualified(Qualifier::Const, boxed(Encoding::String)));
+        let inout = parse("rN^@");
+        assert_eq!(inout,
+                   Encoding::Qualified(Qualifier::Const,
+                                       boxed(Encoding::Qualified(Qualifier::InOut,
+                                                                 boxed(Encoding::Pointer(boxed(Encoding::Object(None))))))));
+        assert_eq!(inout.unqualified(), &parse("^@"));
+        assert_eq!(parse("@\"NSString\""), Encoding::Object(Some("NSString".to_string())));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for s in &["{CGRect={CGPoint=dd}{CGSize=dd}}", "^{__CFString}", "r^v", "[12^{?=iI}]",
+                   "(_NSRange=QQ)", "@\"NSWindow\"", "Vv", "{_NSAffineTransformStruct=dddddd}"] {
+            assert_eq!(parse(s).to_string(), *s);
+        }
+    }
+
+    #[test]
+    fn test_method_signatures() {
+        let signature: MethodSignature = "v24@0:8@16".parse().unwrap();
+        assert_eq!(signature.return_type, Encoding::Void);
+        assert_eq!(signature.frame_size, Some(24));
+        assert_eq!(signature.arguments,
+                   vec![Argument { encoding: Encoding::Object(None), offset: Some(0) },
+                        Argument { encoding: Encoding::Sel, offset: Some(8) },
+                        Argument { encoding: Encoding::Object(None), offset: Some(16) }]);
+        assert_eq!(signature.explicit_arguments().len(), 1);
+        assert_eq!(signature.to_string(), "v24@0:8@16");
+
+        let signature: MethodSignature = "{CGRect={CGPoint=dd}{CGSize=dd}}16@0:8".parse().unwrap();
+        assert_eq!(signature.return_type, parse("{CGRect={CGPoint=dd}{CGSize=dd}}"));
+        assert_eq!(signature.frame_size, Some(16));
+        assert!(signature.explicit_arguments().is_empty());
+
+        let signature: MethodSignature = "c@:{_NSRange=QQ}^@".parse().unwrap();
+        assert_eq!(signature.frame_size, None);
+        assert_eq!(signature.arguments.len(), 4);
+        assert!(signature.arguments.iter().all(|argument| argument.offset.is_none()));
+        assert_eq!(signature.to_string(), "c@:{_NSRange=QQ}^@");
+    }
+
+    #[test]
+    fn test_errors() {
+        let table = [
+            ("", 0),
+            ("x", 0),
+            ("ii", 1),
+            ("{CGRect=dd", 10),
+            ("{CGRect", 7),
+            ("[d]", 1),
+            ("^", 1),
+            ("@\"NSString", 10),
+            ("b", 1),
+            ("{?=\"a\"i\"b\"}", 10),
+            ("{?=\"a\"ii}", 7),
+            ("{?=\"a", 5),
+            ("@?<v@?", 6),
+        ];
+        for &(s, position) in &table {
+            let err = s.parse::<Encoding>().unwrap_err();
+            assert_eq!((s, err.position()), (s, position));
+        }
+        assert!("v24@0:8@x".parse::<MethodSignature>().is_err());
+        assert!("[99999999999999999999999i]".parse::<Encoding>().is_err());
+        assert_eq!("{CGRect=dd".parse::<Encoding>().unwrap_err().to_string(),
+                   "unexpected end of encoding at offset 10");
+    }
+
+    #[test]
+    fn test_equivalence() {
+        let full = parse("^{CGRect={CGPoint=dd}{CGSize=dd}}");
+        assert!(parse("^{CGRect}").is_equivalent(&full));
+        assert!(full.is_equivalent(&parse("r^{CGRect}")));
+        assert!(parse("@\"NSString\"").is_equivalent(&parse("@")));
+        assert!(parse("{?=dd}").is_equivalent(&parse("{CGPoint=dd}")));
+        assert!(!parse("{CGPoint=dd}").is_equivalent(&parse("{CGSize=dd}")));
+        assert!(!parse("{CGPoint=dd}").is_equivalent(&parse("{CGPoint=ff}")));
+        assert!(!parse("@").is_equivalent(&parse("@?")));
+        assert!(!parse("q").is_equivalent(&parse("Q")));
+    }
+
+    #[test]
+    fn test_objc_encodings() {
+        let rect = Encoding::of::<NSRect>();
+        assert_eq!(rect, NSRect::encode());
+        assert_eq!(NSRect::encode(), rect);
+        match rect {
+            Encoding::Struct(_, ref fields) => {
+                assert_eq!(fields, &vec![Encoding::of::<NSPoint>(), Encoding::of::<NSSize>()]);
+            }
+            _ => panic!("NSRect is not encoded as a struct: {}", rect),
+        }
+        assert!(Encoding::of::<NSRange>() != NSRect::encode());
+        assert_eq!(Encoding::Qualified(Qualifier::Const, boxed(Encoding::String)),
+                   <*const i8>::encode());
+    }
+
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn test_objc_encodings_64() {
+        assert_eq!(parse("{CGRect={CGPoint=dd}{CGSize=dd}}"), NSRect::encode());
+        assert_eq!(parse("{CGRect}"), NSRect::encode());
+        assert_eq!(parse("{_NSRange=QQ}"), NSRange::encode());
+    }
+
+    #[test]
+    fn test_rust_signatures() {
+        let passed = MethodSignature::of::<(NSRect, NSUInteger, NSUInteger, BOOL), id>();
+        assert_eq!(passed.explicit_arguments().len(), 4);
+        assert_eq!(passed.return_type, Encoding::Object(None));
+        assert_eq!(passed.arguments[1].encoding, Encoding::Sel);
+        assert_eq!(passed.arguments[2].encoding, Encoding::of::<NSRect>());
+
+        let passed = MethodSignature::of::<(), ()>();
+        assert_eq!(passed.to_string(), "v@:");
+        let passed = MethodSignature::of::<(SEL,), BOOL>();
+        assert_eq!(passed.arguments.len(), 3);
+    }
+
+    #[test]
+    fn test_accepts() {
+        assert!(signature("v24@0:8@16").accepts(&signature("v@:@")));
+        assert!(signature("v@:@").accepts(&signature("v@:#")));
+        assert!(signature("#@:").accepts(&signature("@@:")));
+        assert!(signature("v@:r^I").accepts(&signature("v@:r^v")));
+        assert!(signature("v@:r*").accepts(&signature("v@:^v")));
+        assert!(signature("c@:@").accepts(&signature("v@:@")));
+        assert!(signature("v@:q").accepts(&signature("v@:Q")));
+        assert!(signature("v@:{CGRect={CGPoint=dd}{CGSize=dd}}")
+                    .accepts(&signature("v@:{CGRect={CGPoint=dd}{CGSize=dd}}")));
+
+        assert!(!signature("v@:@").accepts(&signature("v@:")));
+        assert!(!signature("v@:").accepts(&signature("v@:@")));
+        assert!(!signature("v@:Q").accepts(&signature("v@:@")));
+        assert!(!signature("v@:I").accepts(&signature("v@:Q")));
+        assert!(!signature("v@:r^I").accepts(&signature("v@:^Q")));
+        assert!(!signature("v@:d").accepts(&signature("v@:f")));
+        assert!(!signature("@@:").accepts(&signature("{CGPoint=dd}@:")));
+        assert!(!signature("{CGPoint=dd}@:").accepts(&signature("v@:")));
+    }
+}
@@ -0,0 +1,66 @@
+#![cfg(all(feature = "exception", target_os = "macos"))]
+
+#[macro_use]
+extern crate cocoa;
+#[macro_use]
+extern crate objc;
+
+#[cfg(test)]
+mod exception {
+    use cocoa::base::{id, nil};
+    use cocoa::exception::{catch_exception, NSExceptionInfo};
+    use cocoa::foundation::{NSArray, NSString};
+    use cocoa::rc::{autoreleasepool, StrongPtr};
+
+    fn info(name: &str, reason: Option<&str>) -> NSExceptionInfo {
+        NSExceptionInfo {
+            name: name.to_owned(),
+            reason: reason.map(|reason| reason.to_owned()),
+            user_info: unsafe { StrongPtr::new(nil) },
+            exception: unsafe { StrongPtr::new(nil) },
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(info("NSRangeException", Some("index 10 beyond bounds")).to_string(),
+                   "NSRangeException: index 10 beyond bounds");
+        assert_eq!(info("NSGenericException", None).to_string(), "NSGenericException");
+    }
+
+    #[test]
+    fn test_no_exception() {
+        assert_eq!(unsafe { catch_exception(|| 42) }.unwrap(), 42);
+    }
+
+    #[test]
+    #[should_panic(expected = "inside the try")]
+    fn test_panic_is_resumed() {
+        unsafe {
+            let _ = catch_exception::<_, ()>(|| panic!("inside the try"));
+        }
+    }
+
+    #[test]
+    fn test_range_exception() {
+        unsafe {
+            let array = autoreleasepool(|pool| NSArray::array(nil, pool).retain());
+            let result: Result<id, _> = try_msg_send![*array, objectAtIndex:10usize];
+            let info = result.unwrap_err();
+            assert_eq!(info.name, "NSRangeException");
+            assert!(info.reason.unwrap().contains("10"));
+            assert!(!info.exception.is_null());
+        }
+    }
+
+    #[test]
+    fn test_unrecognized_selector() {
+        unsafe {
+            let string = StrongPtr::new(NSString::alloc(nil).init_str("not an array"));
+            let info = catch_exception(|| NSArray::arrayByAddingObjectsFromArray(*string, nil))
+                .unwrap_err();
+            assert_eq!(info.name, "NSInvalidArgumentException");
+            assert!(info.reason.unwrap().contains("unrecognized selector"));
+        }
+    }
+}
@@ -1,13 +1,34 @@
 extern crate cocoa;
+#[cfg(target_os = "macos")]
+extern crate core_graphics;
+extern crate objc;
+#[cfg(feature = "euclid")]
+extern crate euclid;
 
 #[cfg(test)]
 mod foundation {
+    // The modules that send messages need Foundation, so they only run on macOS; the
+    // geometry, range and date arithmetic is tested on any host.
+    #[cfg(target_os = "macos")]
     mod nsstring {
-        use cocoa::foundation::{NSString};
-        use cocoa::base::{nil};
+        use cocoa::foundation::{NSComparisonResult, NSRange, NSString, NSStringRef};
+        use cocoa::base::{id, nil, NO, YES};
+        use cocoa::object::{AsId, ObjCClass};
+        use cocoa::rc::{autoreleasepool, Retained};
+        use std::borrow::Cow;
+        use std::cmp::Ordering;
+        use std::ffi::CStr;
         use std::slice;
         use std::str;
 
+        fn string(s: &str) -> Retained<NSStringRef> {
+            Retained::from(s)
+        }
+
+        unsafe fn retained(obj: id) -> Retained<NSStringRef> {
+            Retained::retain(NSStringRef::from_id(obj))
+        }
+
         #[test]
         fn test_utf8() {
             let expected = "Iñtërnâtiônàlizætiøn";
@@ -41,6 +62,16 @@ mod foundation {
             }
         }
 
+        #[test]
+        fn test_autoreleased() {
+            let kept = autoreleasepool(|pool| unsafe {
+                let string = NSString::stringWithUTF8String_(nil, pool, CStr::from_bytes_with_nul(b"abc\0").unwrap());
+                assert!(string.get().is_some());
+                retained(*string.retain())
+            });
+            assert_eq!(kept, "abc");
+        }
+
         #[test]
         fn test_append_by_appending_string() {
             let initial_str = "Iñtërnâtiônàlizætiøn";
@@ -55,5 +86,1209 @@ mod foundation {
                 assert!(objc_string == expected);
             }
         }
+
+        #[test]
+        fn test_bridging() {
+            let expected = "Iñtërnâtiônàlizætiøn";
+            let built = string(expected);
+            assert_eq!(String::from(&built), expected);
+            assert_eq!(unsafe { built.to_str() }, Cow::Borrowed(expected));
+            assert_eq!(built.to_string(), expected);
+            assert_eq!(format!("{:?}", built), format!("{:?}", expected));
+            assert!(built == expected);
+            assert!(built != "Internationalization");
+            let nil_string = unsafe { Retained::new(NSStringRef::nil()) };
+            assert!(nil_string != "");
+            assert_eq!(unsafe { nil_string.to_str() }, "");
+            assert_eq!(format!("{:?}", nil_string), "nil");
+        }
+
+        #[test]
+        fn test_lengths() {
+            // Two UTF-16 code units, four UTF-8 bytes.
+            let built = string("\u{1F600}");
+            unsafe {
+                assert_eq!(built.length(), 2);
+                assert_eq!(built.len(), 4);
+            }
+        }
+
+        #[test]
+        fn test_utf16() {
+            let expected: Vec<u16> = "naïve \u{1F600}".encode_utf16().collect();
+            unsafe {
+                let built = Retained::new(NSStringRef::from_id(NSString::alloc(nil)).init_utf16(&expected));
+                assert_eq!(built.utf16(), expected);
+                assert_eq!(built.characterAtIndex_(2), 'ï' as u16);
+                assert_eq!(built, "naïve \u{1F600}");
+
+                // An unpaired surrogate has no UTF-8 representation.
+                let unpaired = Retained::new(NSStringRef::from_id(NSString::alloc(nil)).init_utf16(&[0x61, 0xD800]));
+                assert_eq!(unpaired.to_str(), "a\u{FFFD}");
+            }
+        }
+
+        #[test]
+        fn test_is_equal_to_string() {
+            unsafe {
+                assert!(string("Hello").isEqualToString("Hello"));
+                assert!(!string("Hello").isEqualToString("hello"));
+            }
+        }
+
+        #[test]
+        fn test_substrings() {
+            let built = string("Hello World!");
+            unsafe {
+                assert_eq!(retained(built.substringToIndex_(5)), "Hello");
+                assert_eq!(retained(built.substringFromIndex_(6)), "World!");
+                assert_eq!(retained(built.substringWithRange_(NSRange::new(6, 5))), "World");
+            }
+        }
+
+        #[test]
+        fn test_compare() {
+            let apple = string("apple");
+            let banana = string("Banana");
+            unsafe {
+                assert_eq!(apple.compare_(apple.as_id()), NSComparisonResult::NSOrderedSame);
+                assert_eq!(Ordering::from(apple.compare_(banana.as_id())), Ordering::Greater);
+                assert_eq!(Ordering::from(apple.caseInsensitiveCompare_(banana.as_id())), Ordering::Less);
+                assert_eq!(apple.hasPrefix_(string("app").as_id()), YES);
+                assert_eq!(apple.hasSuffix_(string("app").as_id()), NO);
+            }
+        }
+
+        #[test]
+        fn test_case() {
+            let built = string("hello wORLD");
+            unsafe {
+                assert_eq!(retained(built.uppercaseString()), "HELLO WORLD");
+                assert_eq!(retained(built.lowercaseString()), "hello world");
+                assert_eq!(retained(built.capitalizedString()), "Hello World");
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    mod nsarray {
+        use cocoa::base::{id, nil, NO, YES};
+        use cocoa::foundation::{NSArray, NSArrayRef, NSFastEnumerationState, NSMutableArray,
+                                NSMutableArrayRef, NSNotFound, NSString, NSStringRef};
+        use cocoa::object::AsId;
+        use cocoa::rc::{autoreleasepool, Retained};
+        use std::mem;
+
+        fn strings(values: &[&str]) -> Vec<Retained<NSStringRef>> {
+            values.iter().map(|&value| Retained::from(value)).collect()
+        }
+
+        fn contents<I: IntoIterator<Item = id>>(objects: I) -> Vec<String> {
+            objects.into_iter().map(|obj| unsafe { NSStringRef::from_id(obj).to_str().into_owned() }).collect()
+        }
+
+        #[test]
+        fn test_enumeration_state_layout() {
+            assert_eq!(mem::size_of::<NSFastEnumerationState>(), 8 * mem::size_of::<usize>());
+        }
+
+        #[test]
+        fn test_query() {
+            let values = strings(&["a", "b", "c"]);
+            let array: Retained<NSArrayRef> = values.iter().map(|value| **value).collect();
+            unsafe {
+                assert_eq!(array.count(), 3);
+                assert_eq!(array.objectAtIndex_(1), values[1].as_id());
+                assert_eq!(array.firstObject(), values[0].as_id());
+                assert_eq!(array.lastObject(), values[2].as_id());
+                assert_eq!(array.containsObject_(values[2].as_id()), YES);
+                assert_eq!(array.indexOfObject_(values[2].as_id()), 2);
+                assert_eq!(array.indexOfObject_(Retained::from("d").as_id()), NSNotFound);
+            }
+        }
+
+        #[test]
+        fn test_autoreleased() {
+            let value = Retained::from("a");
+            autoreleasepool(|pool| unsafe {
+                let empty = NSArray::array(nil, pool);
+                assert!(empty.get().is_some());
+                assert_eq!(empty.retain().count(), 0);
+                assert_eq!(empty.retain().firstObject(), nil);
+
+                let array = NSArray::arrayWithObject(nil, pool, value.as_id()).retain();
+                assert_eq!(array.count(), 1);
+                assert_eq!(array.objectAtIndex_(0), value.as_id());
+                let array = NSMutableArray::arrayWithCapacity(nil, pool, 4).retain();
+                assert_eq!(array.count(), 0);
+            });
+        }
+
+        #[test]
+        fn test_iter() {
+            // More objects than fit in one batch.
+            let values: Vec<String> = (0..40).map(|i| i.to_string()).collect();
+            let objects: Vec<_> = values.iter().map(|value| Retained::from(&value[..])).collect();
+            let array: Retained<NSArrayRef> = objects.iter().map(|obj| **obj).collect();
+            assert_eq!(contents(&array), values);
+            let empty = autoreleasepool(|pool| unsafe { NSArray::array(nil, pool).retain() });
+            assert_eq!(contents(unsafe { NSArrayRef::from_id(*empty).iter() }), Vec::<String>::new());
+            assert_eq!(unsafe { NSArrayRef::from_id(nil).iter() }.count(), 0);
+        }
+
+        #[test]
+        fn test_iter_outlives_array() {
+            let values = strings(&["a", "b"]);
+            let array: Retained<NSArrayRef> = values.iter().map(|value| **value).collect();
+            // The iterator retains the array, so it's still alive here.
+            let iter = (&array).into_iter();
+            drop(array);
+            assert_eq!(contents(iter), ["a", "b"]);
+        }
+
+        #[test]
+        fn test_mutation() {
+            let values = strings(&["a", "b", "c", "d"]);
+            let array: Retained<NSMutableArrayRef> = values[..2].iter().map(|value| **value).collect();
+            unsafe {
+                array.addObject_(values[3].as_id());
+                array.insertObject_atIndex_(values[2].as_id(), 2);
+                assert_eq!(contents(&array), ["a", "b", "c", "d"]);
+
+                array.exchangeObjectAtIndex_withObjectAtIndex_(0, 3);
+                array.replaceObjectAtIndex_withObject_(1, values[2].as_id());
+                assert_eq!(contents(&array), ["d", "c", "c", "a"]);
+
+                array.removeObject_(values[2].as_id());
+                assert_eq!(contents(&array), ["d", "a"]);
+                array.removeObjectAtIndex_(0);
+                array.removeLastObject();
+                assert_eq!(array.count(), 0);
+
+                autoreleasepool(|pool| {
+                    let objects = NSArray::arrayWithObjects(nil, pool, &[values[0].as_id()]);
+                    array.addObjectsFromArray_(*objects.retain());
+                });
+                let added = NSArrayRef::from_id(array.arrayByAddingObject_(values[1].as_id()));
+                assert_eq!(contents(added.iter()), ["a", "b"]);
+                array.removeAllObjects();
+                assert_eq!(array.containsObject_(values[0].as_id()), NO);
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "mutated while being iterated over")]
+        fn test_mutation_while_iterating() {
+            let values = strings(&["a", "b"]);
+            let array: Retained<NSMutableArrayRef> = values.iter().map(|value| **value).collect();
+            for _ in &array {
+                unsafe { array.addObject_(values[0].as_id()) };
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "can't contain nil")]
+        fn test_collect_nil() {
+            let _: Retained<NSArrayRef> = vec![nil].into_iter().collect();
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    mod nsdata {
+        use cocoa::base::{nil, NO, YES};
+        use cocoa::foundation::{NSData, NSDataRef, NSMutableData, NSMutableDataRef, NSRange};
+        use cocoa::object::AsId;
+        use cocoa::rc::{autoreleasepool, Retained};
+
+        #[test]
+        fn test_copy() {
+            let bytes = [1u8, 2, 3];
+            let data = Retained::<NSDataRef>::from(&bytes[..]);
+            unsafe {
+                assert_eq!(data.length(), 3);
+                assert!(data.bytes() as *const u8 != bytes.as_ptr());
+                assert_eq!(data.as_slice(), &bytes[..]);
+                assert_eq!(Vec::from(&data), bytes.to_vec());
+                let empty = autoreleasepool(|pool| NSData::data(nil, pool).retain());
+                assert_eq!(NSDataRef::from_id(*empty).as_slice(), &[] as &[u8]);
+                assert_eq!(NSDataRef::from_id(nil).to_vec(), Vec::<u8>::new());
+            }
+        }
+
+        #[test]
+        fn test_no_copy() {
+            let bytes: Vec<u8> = (0..255).collect();
+            let ptr = bytes.as_ptr();
+            let data = Retained::<NSDataRef>::from(bytes);
+            unsafe {
+                assert_eq!(data.bytes() as *const u8, ptr);
+                assert_eq!(data.as_slice().len(), 255);
+                assert_eq!(data.as_slice()[254], 254);
+                let copy = Retained::<NSDataRef>::from(data.as_slice());
+                assert_eq!(data.isEqualToData_(copy.as_id()), YES);
+            }
+            // Dropping the data frees the vector's buffer through the deallocator.
+            drop(data);
+            assert_eq!(unsafe { Retained::<NSDataRef>::from(Vec::new()).length() }, 0);
+        }
+
+        #[test]
+        fn test_mutation() {
+            let mut data = Retained::<NSMutableDataRef>::from(&b"abc"[..]);
+            unsafe {
+                data.extend_from_slice(b"def");
+                data.appendData_(Retained::<NSDataRef>::from(&b"g"[..]).as_id());
+                assert_eq!(data.as_slice(), b"abcdefg");
+
+                data.as_mut_slice()[0] = b'A';
+                data.replaceBytesInRange_withBytes_(NSRange::new(1, 2), b"BC".as_ptr() as *const _);
+                data.resetBytesInRange_(NSRange::new(6, 1));
+                assert_eq!(data.as_slice(), b"ABCdef\0");
+
+                data.setLength_(2);
+                data.increaseLengthBy_(1);
+                assert_eq!(data.to_vec(), b"AB\0");
+                assert_eq!(data.isEqualToData_(Retained::<NSDataRef>::from(&b"AB"[..]).as_id()), NO);
+
+                let empty = autoreleasepool(|pool| NSMutableData::dataWithCapacity(nil, pool, 16).retain());
+                let mut empty = Retained::retain(NSMutableDataRef::from_id(*empty));
+                assert_eq!(empty.as_mut_slice(), &mut [] as &mut [u8]);
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    mod nsdictionary {
+        use cocoa::base::{id, nil};
+        use cocoa::foundation::{NSDictionary, NSDictionaryRef, NSMutableDictionary,
+                                NSMutableDictionaryRef, NSNumberRef, NSString, NSStringRef};
+        use cocoa::object::AsId;
+        use cocoa::rc::{autoreleasepool, Retained};
+        use std::collections::HashMap;
+
+        fn string(obj: id) -> String {
+            unsafe { NSStringRef::from_id(obj).to_str().into_owned() }
+        }
+
+        fn sorted_pairs<I: IntoIterator<Item = (id, id)>>(pairs: I) -> Vec<(String, String)> {
+            let mut pairs: Vec<_> = pairs.into_iter().map(|(key, value)| {
+                (string(key), string(value))
+            }).collect();
+            pairs.sort();
+            pairs
+        }
+
+        fn entries(values: &[(&str, &str)]) -> Vec<(Retained<NSStringRef>, Retained<NSStringRef>)> {
+            values.iter().map(|&(key, value)| (Retained::from(key), Retained::from(value))).collect()
+        }
+
+        #[test]
+        fn test_lookup() {
+            let entries = entries(&[("a", "1"), ("b", "2")]);
+            let dictionary: Retained<NSDictionaryRef> =
+                entries.iter().map(|&(ref key, ref value)| (**key, **value)).collect();
+            unsafe {
+                assert_eq!(dictionary.count(), 2);
+                assert_eq!(dictionary.objectForKey_(entries[1].0.as_id()), entries[1].1.as_id());
+                assert_eq!(dictionary.get_str("a"), entries[0].1.as_id());
+                assert_eq!(dictionary.get_str("c"), nil);
+                autoreleasepool(|pool| {
+                    let empty = NSDictionary::dictionary(nil, pool).retain();
+                    assert_eq!(empty.count(), 0);
+                    assert_eq!(empty.get_str("a"), nil);
+                });
+            }
+        }
+
+        #[test]
+        fn test_iter() {
+            let entries = entries(&[("a", "1"), ("b", "2"), ("c", "3")]);
+            let dictionary: Retained<NSDictionaryRef> =
+                entries.iter().map(|&(ref key, ref value)| (**key, **value)).collect();
+            let expected: Vec<_> = [("a", "1"), ("b", "2"), ("c", "3")].iter()
+                .map(|&(key, value)| (key.to_owned(), value.to_owned()))
+                .collect();
+            assert_eq!(sorted_pairs(&dictionary), expected);
+
+            let mut keys: Vec<_> = unsafe { dictionary.keys() }.map(string).collect();
+            keys.sort();
+            assert_eq!(keys, ["a", "b", "c"]);
+            let mut values: Vec<_> = unsafe { dictionary.values() }.map(string).collect();
+            values.sort();
+            assert_eq!(values, ["1", "2", "3"]);
+            assert_eq!(unsafe { NSDictionaryRef::from_id(nil).iter() }.count(), 0);
+        }
+
+        #[test]
+        fn test_iter_outlives_dictionary() {
+            let entries = entries(&[("a", "1")]);
+            let dictionary: Retained<NSDictionaryRef> =
+                entries.iter().map(|&(ref key, ref value)| (**key, **value)).collect();
+            // The iterator retains the dictionary, so it's still alive here.
+            let iter = (&dictionary).into_iter();
+            drop(dictionary);
+            assert_eq!(sorted_pairs(iter), [("a".to_owned(), "1".to_owned())]);
+        }
+
+        #[test]
+        fn test_mutation() {
+            let entries = entries(&[("a", "1"), ("b", "2")]);
+            let dictionary: Retained<NSMutableDictionaryRef> =
+                entries[..1].iter().map(|&(ref key, ref value)| (**key, **value)).collect();
+            unsafe {
+                dictionary.setObject_forKey_(entries[1].1.as_id(), entries[1].0.as_id());
+                assert_eq!(dictionary.count(), 2);
+
+                // Replaces the value for an equal key.
+                dictionary.setObject_forKey_(entries[1].1.as_id(), Retained::from("a").as_id());
+                assert_eq!(dictionary.get_str("a"), entries[1].1.as_id());
+
+                dictionary.removeObjectForKey_(entries[0].0.as_id());
+                assert_eq!(sorted_pairs(&dictionary), [("b".to_owned(), "2".to_owned())]);
+
+                dictionary.removeAllObjects();
+                assert_eq!(dictionary.count(), 0);
+                autoreleasepool(|pool| {
+                    let other = NSDictionary::dictionaryWithObject_forKey_(nil, pool, entries[0].1.as_id(),
+                                                                           entries[0].0.as_id());
+                    dictionary.addEntriesFromDictionary_(*other.retain());
+                });
+                assert_eq!(dictionary.get_str("a"), entries[0].1.as_id());
+            }
+        }
+
+        #[test]
+        fn test_hash_map() {
+            let values: Vec<_> = ["1", "2"].iter().map(|&value| Retained::from(value)).collect();
+            let mut map = HashMap::new();
+            map.insert("a", *values[0]);
+            map.insert("b", *values[1]);
+
+            let dictionary = Retained::<NSDictionaryRef>::from(&map);
+            let round_trip = unsafe { dictionary.to_hash_map() };
+            assert_eq!(round_trip.len(), 2);
+            assert_eq!(round_trip["a"], values[0].as_id());
+            assert_eq!(round_trip["b"], values[1].as_id());
+
+            let mutable = Retained::<NSMutableDictionaryRef>::from(&map);
+            unsafe {
+                mutable.removeObjectForKey_(Retained::from("a").as_id());
+                assert_eq!(mutable.to_hash_map().keys().collect::<Vec<_>>(), ["b"]);
+            }
+        }
+
+        #[test]
+        fn test_hash_map_non_string_keys() {
+            let key = Retained::<NSNumberRef>::from(42i32);
+            let value = Retained::<NSStringRef>::from("answer");
+            let dictionary: Retained<NSDictionaryRef> = vec![(*key, *value)].into_iter().collect();
+            let map = unsafe { dictionary.to_hash_map() };
+            assert_eq!(map.len(), 1);
+            assert_eq!(map["42"], value.as_id());
+        }
+
+        #[test]
+        #[should_panic(expected = "can't contain nil")]
+        fn test_collect_nil() {
+            let _: Retained<NSDictionaryRef> = vec![(nil, nil)].into_iter().collect();
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    mod nsnumber {
+        use cocoa::base::{nil, NO, YES};
+        use cocoa::foundation::{NSComparisonResult, NSNumber, NSNumberRef, NSNumberScalar,
+                                NSNumberValue, NSPoint, NSRange, NSRect, NSSize, NSValue,
+                                NSValueRef};
+        use cocoa::object::AsId;
+        use cocoa::rc::Retained;
+        use std::ffi::CStr;
+
+        #[test]
+        fn test_integer_from_value() {
+            assert_eq!(u8::from_value(NSNumberValue::Signed(255)), Some(255));
+            assert_eq!(u8::from_value(NSNumberValue::Signed(256)), None);
+            assert_eq!(u64::from_value(NSNumberValue::Signed(-1)), None);
+            assert_eq!(i64::from_value(NSNumberValue::Unsigned(u64::max_value())), None);
+            assert_eq!(u64::from_value(NSNumberValue::Unsigned(u64::max_value())),
+                       Some(u64::max_value()));
+            assert_eq!(i8::from_value(NSNumberValue::Signed(-128)), Some(-128));
+            assert_eq!(i32::from_value(NSNumberValue::Float(-3.0)), Some(-3));
+            assert_eq!(i32::from_value(NSNumberValue::Float(0.5)), None);
+            assert_eq!(i64::from_value(NSNumberValue::Float(9223372036854775808.0)), None);
+            assert_eq!(u32::from_value(NSNumberValue::Float(::std::f64::NAN)), None);
+            assert_eq!(i32::from_value(NSNumberValue::Bool(true)), None);
+        }
+
+        #[test]
+        fn test_float_and_bool_from_value() {
+            assert_eq!(f64::from_value(NSNumberValue::Float(0.25)), Some(0.25));
+            assert_eq!(f32::from_value(NSNumberValue::Signed(-2)), Some(-2.0));
+            assert_eq!(f64::from_value(NSNumberValue::Unsigned(7)), Some(7.0));
+            assert_eq!(f64::from_value(NSNumberValue::Bool(false)), None);
+            assert_eq!(bool::from_value(NSNumberValue::Bool(true)), Some(true));
+            assert_eq!(bool::from_value(NSNumberValue::Signed(1)), None);
+        }
+
+        #[test]
+        fn test_round_trip() {
+            unsafe {
+                assert_eq!(Retained::<NSNumberRef>::from(-5i8).get::<i8>(), Some(-5));
+                assert_eq!(Retained::<NSNumberRef>::from(200u8).get::<u8>(), Some(200));
+                assert_eq!(Retained::<NSNumberRef>::from(-300i16).get::<i64>(), Some(-300));
+                assert_eq!(Retained::<NSNumberRef>::from(u64::max_value()).get::<u64>(),
+                           Some(u64::max_value()));
+                assert_eq!(Retained::<NSNumberRef>::from(u64::max_value()).get::<i64>(), None);
+                assert_eq!(Retained::<NSNumberRef>::from(1.5f64).value(), NSNumberValue::Float(1.5));
+                assert_eq!(Retained::<NSNumberRef>::from(1.5f32).get::<f32>(), Some(1.5));
+                assert_eq!(Retained::<NSNumberRef>::from(-7isize).value(), NSNumberValue::Signed(-7));
+                assert_eq!(Retained::<NSNumberRef>::from(true).value(), NSNumberValue::Bool(true));
+                assert_eq!(Retained::<NSNumberRef>::from(false).get::<bool>(), Some(false));
+                assert_eq!(Retained::<NSNumberRef>::from(1i8).get::<bool>(), None);
+                assert_eq!(NSNumberRef::from_id(NSNumber::numberWithBool(nil, YES)).boolValue(), YES);
+            }
+        }
+
+        #[test]
+        fn test_compare() {
+            let one = Retained::<NSNumberRef>::from(1u32);
+            let two = Retained::<NSNumberRef>::from(2.0f64);
+            unsafe {
+                assert_eq!(one.compare_(two.as_id()), NSComparisonResult::NSOrderedAscending);
+                assert_eq!(one.isEqualToNumber_(Retained::<NSNumberRef>::from(1.0f64).as_id()), YES);
+                assert_eq!(one.isEqualToNumber_(two.as_id()), NO);
+                assert_eq!(two.integerValue(), 2);
+            }
+        }
+
+        #[test]
+        fn test_geometry_values() {
+            let rect = NSRect::new(NSPoint::new(1., 2.), NSSize::new(3., 4.));
+            unsafe {
+                let value = NSValueRef::from_id(NSValue::valueWithRect(nil, rect));
+                assert_eq!(value.rectValue(), rect);
+                assert_eq!(CStr::from_ptr(value.objCType()).to_bytes()[0], b'{');
+                let point = NSValueRef::from_id(NSValue::valueWithPoint(nil, rect.origin));
+                assert_eq!(point.pointValue(), rect.origin);
+                let size = NSValueRef::from_id(NSValue::valueWithSize(nil, rect.size));
+                assert_eq!(size.sizeValue(), rect.size);
+                let range = NSRange::new(5, 10);
+                let value = NSValueRef::from_id(NSValue::valueWithRange(nil, range));
+                assert_eq!(value.rangeValue(), range);
+                assert_eq!(value.isEqualToValue_(NSValue::valueWithRange(nil, range)), YES);
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    mod nsurl {
+        use cocoa::base::{id, nil, NO, YES};
+        use cocoa::foundation::{NSNumber, NSNumberRef, NSString, NSStringRef, NSURL, NSURLRef};
+        use cocoa::object::AsId;
+        use cocoa::rc::Retained;
+        use std::env;
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        use std::path::{Path, PathBuf};
+
+        fn string(obj: id) -> String {
+            unsafe { NSStringRef::from_id(obj).to_str().into_owned() }
+        }
+
+        #[test]
+        fn test_components() {
+            let url: Retained<NSURLRef> =
+                "https://user@example.com:8080/a%20b/c.txt?q=1#top".parse().unwrap();
+            unsafe {
+                assert_eq!(string(url.scheme()), "https");
+                assert_eq!(string(url.user()), "user");
+                assert_eq!(string(url.host()), "example.com");
+                assert_eq!(NSNumberRef::from_id(url.port()).get::<u16>(), Some(8080));
+                assert_eq!(string(url.path()), "/a b/c.txt");
+                assert_eq!(string(url.query()), "q=1");
+                assert_eq!(string(url.fragment()), "top");
+                assert_eq!(string(url.lastPathComponent()), "c.txt");
+                assert_eq!(string(url.pathExtension()), "txt");
+                assert_eq!(url.isFileURL(), NO);
+                assert_eq!(url.to_path_buf(), None);
+            }
+            assert_eq!(url.to_string(), "https://user@example.com:8080/a%20b/c.txt?q=1#top");
+            assert!("http://[".parse::<Retained<NSURLRef>>().is_err());
+        }
+
+        #[test]
+        fn test_relative() {
+            let base: Retained<NSURLRef> = "https://example.com/a/b".parse().unwrap();
+            unsafe {
+                let url = NSURLRef::from_id(
+                    NSURL::URLWithString_relativeToURL_(nil, Retained::from("c/d").as_id(),
+                                                        base.as_id()));
+                assert_eq!(string(url.path()), "/a/c/d");
+                assert_eq!(url.baseURL(), base.as_id());
+                let url = NSURLRef::from_id(url.URLByDeletingLastPathComponent());
+                let absolute = Retained::retain(NSURLRef::from_id(url.absoluteURL()));
+                assert_eq!(absolute.to_string(), "https://example.com/a/c/");
+            }
+        }
+
+        #[test]
+        fn test_paths() {
+            let dir = env::temp_dir();
+            let url = Retained::<NSURLRef>::from(dir.as_path());
+            unsafe {
+                assert_eq!(url.isFileURL(), YES);
+                assert_eq!(url.to_path_buf().map(|path| path.canonicalize().unwrap()),
+                           Some(dir.canonicalize().unwrap()));
+                let child = NSURLRef::from_id(url.URLByAppendingPathComponent_(
+                    Retained::from("file.txt").as_id()));
+                assert_eq!(child.to_path_buf().unwrap().file_name(), Some(OsStr::new("file.txt")));
+
+                let reference = NSURLRef::from_id(url.fileReferenceURL());
+                assert_eq!(reference.isFileReferenceURL(), YES);
+                assert_eq!(reference.to_path_buf().map(|path| path.canonicalize().unwrap()),
+                           Some(dir.canonicalize().unwrap()));
+            }
+
+            let path = Path::new("/tmp/caf\u{65}\u{301}/\u{fc}ber.txt");
+            let url = Retained::<NSURLRef>::from(path);
+            assert_eq!(unsafe { url.to_path_buf() }, Some(path.to_owned()));
+
+            let path = PathBuf::from(OsStr::from_bytes(b"/tmp/not-utf8-\xff"));
+            let url = Retained::<NSURLRef>::from(path.as_path());
+            assert_eq!(unsafe { url.to_path_buf() }, Some(path));
+        }
+
+        #[test]
+        #[should_panic(expected = "nul byte")]
+        fn test_path_with_nul() {
+            let _ = Retained::<NSURLRef>::from(Path::new("/tmp/a\0b"));
+        }
+    }
+
+    mod nsdate {
+        use cocoa::foundation::{duration_from_time_interval, system_time_since_reference_date,
+                                time_interval_from_duration, time_interval_since_reference_date,
+                                NSTimeIntervalSince1970};
+        use std::f64;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        #[test]
+        fn test_reference_date() {
+            assert_eq!(NSTimeIntervalSince1970, 978307200.0);
+            assert_eq!(time_interval_since_reference_date(UNIX_EPOCH), -978307200.0);
+            let reference = UNIX_EPOCH + Duration::from_secs(978307200);
+            assert_eq!(time_interval_since_reference_date(reference), 0.0);
+            assert_eq!(system_time_since_reference_date(0.0), Some(reference));
+            assert_eq!(system_time_since_reference_date(-978307200.0), Some(UNIX_EPOCH));
+        }
+
+        #[test]
+        fn test_system_time_round_trip() {
+            // 2021-01-01, 1990-06-15 and 1960-03-01, with fractional seconds.
+            let times = [UNIX_EPOCH + Duration::new(1609459200, 250_000_000),
+                         UNIX_EPOCH + Duration::new(645408000, 500_000_000),
+                         UNIX_EPOCH - Duration::new(310003200, 750_000_000)];
+            for &time in &times {
+                let interval = time_interval_since_reference_date(time);
+                assert_eq!(system_time_since_reference_date(interval), Some(time));
+            }
+            assert_eq!(time_interval_since_reference_date(times[0]), 631152000.25);
+            assert_eq!(time_interval_since_reference_date(times[2]), -1288310400.75);
+            assert_eq!(system_time_since_reference_date(f64::NAN), None);
+            assert_eq!(system_time_since_reference_date(f64::INFINITY), None);
+        }
+
+        #[test]
+        fn test_durations() {
+            assert_eq!(time_interval_from_duration(Duration::new(3, 500_000_000)), 3.5);
+            assert_eq!(time_interval_from_duration(Duration::from_millis(1)), 0.001);
+            assert_eq!(duration_from_time_interval(3.5), Some(Duration::new(3, 500_000_000)));
+            assert_eq!(duration_from_time_interval(0.001), Some(Duration::from_millis(1)));
+            assert_eq!(duration_from_time_interval(0.9999999999), Some(Duration::from_secs(1)));
+            assert_eq!(duration_from_time_interval(0.0), Some(Duration::from_secs(0)));
+            assert_eq!(duration_from_time_interval(-0.5), None);
+            assert_eq!(duration_from_time_interval(f64::NAN), None);
+            assert_eq!(duration_from_time_interval(1e20), None);
+        }
+
+        #[test]
+        #[cfg(target_os = "macos")]
+        fn test_dates() {
+            use cocoa::base::{nil, NO, YES};
+            use cocoa::foundation::{NSComparisonResult, NSDate, NSDateRef};
+            use cocoa::object::AsId;
+            use cocoa::rc::Retained;
+            use std::time::SystemTime;
+
+            let time = UNIX_EPOCH + Duration::from_secs(1609459200);
+            let date = Retained::<NSDateRef>::from(time);
+            unsafe {
+                assert_eq!(date.timeIntervalSince1970(), 1609459200.0);
+                assert_eq!(date.timeIntervalSinceReferenceDate(), 631152000.0);
+                assert_eq!(date.to_system_time(), Some(time));
+                assert!(date.timeIntervalSinceNow() < 0.0);
+
+                let later = NSDateRef::from_id(date.dateByAddingTimeInterval_(60.0));
+                assert_eq!(later.timeIntervalSinceDate_(date.as_id()), 60.0);
+                assert_eq!(date.compare_(later.as_id()), NSComparisonResult::NSOrderedAscending);
+                assert_eq!(date.earlierDate_(later.as_id()), date.as_id());
+                assert_eq!(date.laterDate_(later.as_id()), later.as_id());
+                assert_eq!(date.isEqualToDate_(later.as_id()), NO);
+                let same = NSDate::dateWithTimeIntervalSince1970_(nil, 1609459200.0);
+                assert_eq!(date.isEqualToDate_(same), YES);
+
+                let now = NSDateRef::from_id(NSDate::date(nil)).to_system_time().unwrap();
+                let elapsed = SystemTime::now().duration_since(now).unwrap_or(Duration::from_secs(0));
+                assert!(elapsed < Duration::from_secs(60));
+                assert!(NSDateRef::from_id(NSDate::distantFuture(nil)).to_system_time().is_some());
+                assert_eq!(NSDateRef::from_id(nil).to_system_time(), None);
+            }
+        }
+    }
+
+    mod nsrect {
+        use cocoa::foundation::{CGFloat, NSPoint, NSSize, NSRect, NSRectEdge};
+
+        fn rect(x: CGFloat, y: CGFloat, width: CGFloat, height: CGFloat) -> NSRect {
+            NSRect::new(NSPoint::new(x, y), NSSize::new(width, height))
+        }
+
+        fn assert_rect(r: NSRect, x: CGFloat, y: CGFloat, width: CGFloat, height: CGFloat) {
+            assert!(r.origin.x == x && r.origin.y == y &&
+                    r.size.width == width && r.size.height == height,
+                    "expected {{{{{}, {}}}, {{{}, {}}}}}, got {{{{{}, {}}}, {{{}, {}}}}}",
+                    x, y, width, height,
+                    r.origin.x, r.origin.y, r.size.width, r.size.height);
+        }
+
+        #[test]
+        fn test_value_semantics() {
+            let r = rect(10., 20., 30., 40.);
+            let copy = r;
+            assert_eq!(r, copy);
+            assert!(r != r.offset(1., 0.));
+            assert!(r.size == NSSize::new(30., 40.));
+            assert_eq!(format!("{:?}", r.origin), "NSPoint { x: 10.0, y: 20.0 }");
+            assert_eq!(NSRectEdge::NSRectMinXEdge, NSRectEdge::NSRectMinXEdge);
+        }
+
+        #[test]
+        #[cfg(target_os = "macos")]
+        fn test_core_graphics_conversions() {
+            use core_graphics::geometry::{CGPoint, CGSize, CGRect};
+
+            let cg = CGRect {
+                origin: CGPoint { x: 1.5, y: -2. },
+                size: CGSize { width: 300., height: 200.25 },
+            };
+            let r: NSRect = cg.into();
+            assert_eq!(r, rect(1.5, -2., 300., 200.25));
+
+            let cg: CGRect = r.into();
+            assert!(cg.origin.x == 1.5 && cg.origin.y == -2.);
+            assert!(cg.size.width == 300. && cg.size.height == 200.25);
+
+            let p: NSPoint = CGPoint { x: 3., y: 4. }.into();
+            assert_eq!(p, NSPoint::new(3., 4.));
+            let s: CGSize = NSSize::new(5., 6.).into();
+            assert!(s.width == 5. && s.height == 6.);
+        }
+
+        #[cfg(feature = "euclid")]
+        #[test]
+        fn test_euclid_conversions() {
+            use euclid::{Point2D, Rect, Size2D, UnknownUnit};
+
+            struct ScreenSpace;
+
+            let e: Rect<CGFloat, ScreenSpace> = rect(1., 2., 3., 4.).into();
+            assert_eq!(e.origin.x, 1.);
+            assert_eq!(e.size.height, 4.);
+            let r: NSRect = e.into();
+            assert_eq!(r, rect(1., 2., 3., 4.));
+
+            let p: NSPoint = Point2D::<CGFloat, UnknownUnit>::new(5., 6.).into();
+            assert_eq!(p, NSPoint::new(5., 6.));
+            let s: Size2D<CGFloat, UnknownUnit> = NSSize::new(7., 8.).into();
+            assert_eq!(s, Size2D::new(7., 8.));
+        }
+
+        #[test]
+        fn test_display() {
+            assert_eq!(rect(10., 20., 300., 200.).to_string(), "{{10, 20}, {300, 200}}");
+            assert_eq!(rect(-0.5, 1.25, 0., 65536.).to_string(),
+                       "{{-0.5, 1.25}, {0, 65536}}");
+            assert_eq!(NSPoint::new(3., 4.5).to_string(), "{3, 4.5}");
+            assert_eq!(NSSize::new(640., 480.).to_string(), "{640, 480}");
+        }
+
+        // Strings from NSStringFromPoint, which formats each CGFloat with `%.17g`.
+        #[cfg(target_pointer_width = "64")]
+        #[test]
+        fn test_display_matches_foundation() {
+            use std::f64;
+
+            let cases: &[(CGFloat, CGFloat, &str)] = &[
+                (0.1, 1e20, "{0.10000000000000001, 1e+20}"),
+                (1. / 3., -0.5, "{0.33333333333333331, -0.5}"),
+                (1e-5, 0.0001, "{1.0000000000000001e-05, 0.0001}"),
+                (123456789.125, -1440.75, "{123456789.125, -1440.75}"),
+                (1e16, 1e17, "{10000000000000000, 1e+17}"),
+                (1e15 + 0.3, 2.5e-7, "{1000000000000000.2, 2.4999999999999999e-07}"),
+                (5e-324, f64::MAX, "{4.9406564584124654e-324, 1.7976931348623157e+308}"),
+                (f64::NAN, -f64::NAN, "{nan, nan}"),
+                (f64::INFINITY, f64::NEG_INFINITY, "{inf, -inf}"),
+                (-0., 0., "{-0, 0}"),
+            ];
+            for &(x, y, expected) in cases {
+                assert_eq!(NSPoint::new(x, y).to_string(), expected);
+            }
+            assert_eq!(rect(0.1, 0.2, 100., 1e20).to_string(),
+                       "{{0.10000000000000001, 0.20000000000000001}, {100, 1e+20}}");
+        }
+
+        // Strings from NSStringFromPoint where CGFloat is a float, formatted with `%g`.
+        #[cfg(target_pointer_width = "32")]
+        #[test]
+        fn test_display_matches_foundation() {
+            assert_eq!(NSPoint::new(0.1, 1e20).to_string(), "{0.1, 1e+20}");
+            assert_eq!(NSPoint::new(1. / 3., 123456789.125).to_string(),
+                       "{0.333333, 1.23457e+08}");
+            assert_eq!(NSPoint::new(1e-5, 65536.).to_string(), "{1e-05, 65536}");
+        }
+
+        #[test]
+        fn test_from_str() {
+            assert_eq!("{{10, 20}, {300, 200}}".parse::<NSRect>().unwrap(),
+                       rect(10., 20., 300., 200.));
+            assert_eq!("{{-0.5,1.25},{.5,2.}}".parse::<NSRect>().unwrap(),
+                       rect(-0.5, 1.25, 0.5, 2.));
+            assert_eq!("{{1e+21, 2E-2}, {3, 4}}".parse::<NSRect>().unwrap(),
+                       rect(1e21, 2e-2, 3., 4.));
+            assert_eq!("{3, 4.5}".parse::<NSPoint>().unwrap(), NSPoint::new(3., 4.5));
+            assert_eq!("{640, 480}".parse::<NSSize>().unwrap(), NSSize::new(640., 480.));
+        }
+
+        #[test]
+        fn test_from_str_lenient() {
+            assert_eq!("10 20 30 40".parse::<NSRect>().unwrap(), rect(10., 20., 30., 40.));
+            assert_eq!("x=10;y=20;w=30;h=40".parse::<NSRect>().unwrap(),
+                       rect(10., 20., 30., 40.));
+            assert_eq!("{{1, 2}}".parse::<NSRect>().unwrap(), rect(1., 2., 0., 0.));
+            assert_eq!("{{1, 2}, {3, 4}, {5, 6}}".parse::<NSRect>().unwrap(),
+                       rect(1., 2., 3., 4.));
+            assert_eq!("garbage".parse::<NSRect>().unwrap(), NSRect::zero());
+            assert_eq!("".parse::<NSPoint>().unwrap(), NSPoint::new(0., 0.));
+            assert_eq!("{- 5, -.}".parse::<NSPoint>().unwrap(), NSPoint::new(5., 0.));
+            assert_eq!("{1e, 2}".parse::<NSPoint>().unwrap(), NSPoint::new(1., 2.));
+        }
+
+        #[test]
+        fn test_string_round_trip() {
+            let r = rect(0.1, -1. / 3., 1440., 877.5);
+            assert_eq!(r.to_string().parse::<NSRect>().unwrap(), r);
+        }
+
+        #[test]
+        fn test_accessors() {
+            let r = rect(10., 20., 30., 40.);
+            assert!(r.min_x() == 10. && r.mid_x() == 25. && r.max_x() == 40.);
+            assert!(r.min_y() == 20. && r.mid_y() == 40. && r.max_y() == 60.);
+            assert!(r.width() == 30. && r.height() == 40.);
+        }
+
+        #[test]
+        fn test_accessors_negative_size() {
+            let r = rect(10., 20., -30., -40.);
+            assert!(r.min_x() == 10. && r.max_x() == -20.);
+            assert!(r.min_y() == 20. && r.max_y() == -20.);
+        }
+
+        #[test]
+        fn test_is_empty() {
+            assert!(!rect(0., 0., 1., 1.).is_empty());
+            assert!(rect(0., 0., 0., 1.).is_empty());
+            assert!(rect(0., 0., 1., 0.).is_empty());
+            assert!(rect(0., 0., -1., 1.).is_empty());
+            assert!(rect(0., 0., 1., CGFloat::NAN).is_empty());
+            assert!(NSRect::zero().is_empty());
+        }
+
+        #[test]
+        fn test_contains_point() {
+            let r = rect(0., 0., 10., 10.);
+            assert!(r.contains_point(&NSPoint::new(0., 0.)));
+            assert!(r.contains_point(&NSPoint::new(5., 5.)));
+            assert!(!r.contains_point(&NSPoint::new(10., 5.)));
+            assert!(!r.contains_point(&NSPoint::new(5., 10.)));
+            assert!(!r.contains_point(&NSPoint::new(-1., 5.)));
+            assert!(!rect(0., 0., -10., 10.).contains_point(&NSPoint::new(-5., 5.)));
+        }
+
+        #[test]
+        fn test_mouse_in_rect() {
+            let r = rect(0., 0., 10., 10.);
+            assert!(r.mouse_in_rect(&NSPoint::new(5., 0.), true));
+            assert!(!r.mouse_in_rect(&NSPoint::new(5., 10.), true));
+            assert!(!r.mouse_in_rect(&NSPoint::new(5., 0.), false));
+            assert!(r.mouse_in_rect(&NSPoint::new(5., 10.), false));
+        }
+
+        #[test]
+        fn test_contains_rect() {
+            let r = rect(0., 0., 10., 10.);
+            assert!(r.contains_rect(&rect(0., 0., 10., 10.)));
+            assert!(r.contains_rect(&rect(2., 2., 3., 3.)));
+            assert!(!r.contains_rect(&rect(2., 2., 10., 3.)));
+            assert!(!r.contains_rect(&rect(2., 2., 0., 3.)));
+        }
+
+        #[test]
+        fn test_intersects() {
+            let r = rect(0., 0., 10., 10.);
+            assert!(r.intersects(&rect(5., 5., 10., 10.)));
+            assert!(!r.intersects(&rect(10., 0., 10., 10.)));
+            assert!(!r.intersects(&rect(5., 5., 0., 10.)));
+            assert!(!r.intersects(&rect(5., 5., -2., 2.)));
+        }
+
+        #[test]
+        fn test_intersection() {
+            let r = rect(0., 0., 10., 10.);
+            assert_rect(r.intersection(&rect(5., -5., 10., 10.)), 5., 0., 5., 5.);
+            assert_rect(r.intersection(&rect(2., 3., 4., 5.)), 2., 3., 4., 5.);
+            assert_rect(r.intersection(&rect(10., 0., 10., 10.)), 0., 0., 0., 0.);
+            assert_rect(r.intersection(&rect(20., 20., 1., 1.)), 0., 0., 0., 0.);
+            assert_rect(r.intersection(&rect(8., 8., -4., -4.)), 0., 0., 0., 0.);
+        }
+
+        #[test]
+        fn test_union() {
+            let r = rect(0., 0., 10., 10.);
+            assert_rect(r.union(&rect(5., -5., 10., 10.)), 0., -5., 15., 15.);
+            assert_rect(r.union(&rect(100., 100., 0., 10.)), 0., 0., 10., 10.);
+            assert_rect(rect(100., 100., -5., 10.).union(&r), 0., 0., 10., 10.);
+            assert_rect(rect(1., 1., 0., 0.).union(&rect(2., 2., -1., 0.)), 0., 0., 0., 0.);
+        }
+
+        #[test]
+        fn test_offset_and_inset() {
+            let r = rect(10., 10., 20., 20.);
+            assert_rect(r.offset(5., -5.), 15., 5., 20., 20.);
+            assert_rect(r.inset(2., 3.), 12., 13., 16., 14.);
+            assert_rect(r.inset(-2., -3.), 8., 7., 24., 26.);
+            assert_rect(r.inset(15., 0.), 25., 10., -10., 20.);
+        }
+
+        #[test]
+        fn test_integral() {
+            assert_rect(rect(0.5, 1.25, 10.25, 9.5).integral(), 0., 1., 11., 10.);
+            assert_rect(rect(-0.5, -1.5, 1., 1.).integral(), -1., -2., 2., 2.);
+            assert_rect(rect(3., 4., 5., 6.).integral(), 3., 4., 5., 6.);
+            assert_rect(rect(3.5, 4.5, 0., 6.).integral(), 0., 0., 0., 0.);
+        }
+
+        #[test]
+        fn test_divide() {
+            let r = rect(0., 0., 100., 50.);
+
+            let (slice, remainder) = r.divide(30., NSRectEdge::NSRectMinXEdge);
+            assert_rect(slice, 0., 0., 30., 50.);
+            assert_rect(remainder, 30., 0., 70., 50.);
+
+            let (slice, remainder) = r.divide(30., NSRectEdge::NSRectMaxXEdge);
+            assert_rect(slice, 70., 0., 30., 50.);
+            assert_rect(remainder, 0., 0., 70., 50.);
+
+            let (slice, remainder) = r.divide(20., NSRectEdge::NSRectMinYEdge);
+            assert_rect(slice, 0., 0., 100., 20.);
+            assert_rect(remainder, 0., 20., 100., 30.);
+
+            let (slice, remainder) = r.divide(20., NSRectEdge::NSRectMaxYEdge);
+            assert_rect(slice, 0., 30., 100., 20.);
+            assert_rect(remainder, 0., 0., 100., 30.);
+        }
+
+        #[test]
+        fn test_divide_clamps_amount() {
+            let r = rect(0., 0., 100., 50.);
+
+            let (slice, remainder) = r.divide(150., NSRectEdge::NSRectMinXEdge);
+            assert_rect(slice, 0., 0., 100., 50.);
+            assert_rect(remainder, 100., 0., 0., 50.);
+
+            let (slice, remainder) = r.divide(-10., NSRectEdge::NSRectMaxYEdge);
+            assert_rect(slice, 0., 50., 100., 0.);
+            assert_rect(remainder, 0., 0., 100., 50.);
+
+            let (slice, remainder) = rect(0., 0., -1., 5.).divide(1., NSRectEdge::NSRectMinXEdge);
+            assert_rect(slice, 0., 0., 0., 0.);
+            assert_rect(remainder, 0., 0., 0., 0.);
+        }
+    }
+
+    mod nsrange {
+        use cocoa::foundation::{NSNotFound, NSRange, NSUInteger};
+        use std::ops::Range;
+
+        fn assert_range(r: NSRange, location: u64, length: u64) {
+            assert!(u64::from(r.location) == location && u64::from(r.length) == length,
+                    "expected {{{}, {}}}, got {{{}, {}}}", location, length, r.location, r.length);
+        }
+
+        #[test]
+        fn test_max_and_contains() {
+            let r = NSRange::new(3, 5);
+            assert!(r.max() == 8);
+            assert!(!r.contains(2));
+            assert!(r.contains(3));
+            assert!(r.contains(7));
+            assert!(!r.contains(8));
+            assert!(!NSRange::new(3, 0).contains(3));
+        }
+
+        #[test]
+        fn test_intersection() {
+            assert_range(NSRange::new(0, 10).intersection(&NSRange::new(5, 10)), 5, 5);
+            assert_range(NSRange::new(5, 10).intersection(&NSRange::new(0, 10)), 5, 5);
+            assert_range(NSRange::new(0, 10).intersection(&NSRange::new(2, 3)), 2, 3);
+            assert_range(NSRange::new(0, 5).intersection(&NSRange::new(5, 5)), 5, 0);
+            assert_range(NSRange::new(0, 5).intersection(&NSRange::new(6, 5)), 0, 0);
+        }
+
+        #[test]
+        fn test_union() {
+            assert_range(NSRange::new(0, 5).union(&NSRange::new(10, 5)), 0, 15);
+            assert_range(NSRange::new(10, 5).union(&NSRange::new(2, 3)), 2, 13);
+            assert_range(NSRange::new(0, 10).union(&NSRange::new(2, 3)), 0, 10);
+        }
+
+        #[test]
+        fn test_near_max() {
+            let end = NSUInteger::MAX;
+            let r = NSRange::new(end - 1, 5);
+            assert!(r.max() == end);
+            assert!(r.contains(end - 1));
+            assert!(!r.contains(end - 2));
+            assert!(NSRange::new(NSNotFound, 1).max() == NSNotFound + 1);
+            assert_range(r.intersection(&NSRange::new(end - 3, 3)), u64::from(end) - 1, 1);
+            assert_range(r.union(&NSRange::new(end - 3, 1)), u64::from(end) - 3, 3);
+            assert_range(r.intersection(&NSRange::new(0, 5)), 0, 0);
+        }
+
+        #[test]
+        fn test_value_semantics() {
+            let r = NSRange::new(3, 5);
+            let copy = r;
+            assert_eq!(r, copy);
+            assert!(r != NSRange::new(3, 6));
+            assert_eq!(format!("{:?}", r), "NSRange { location: 3, length: 5 }");
+        }
+
+        #[test]
+        fn test_display() {
+            assert_eq!(NSRange::new(3, 5).to_string(), "{3, 5}");
+        }
+
+        #[test]
+        fn test_from_str() {
+            assert_eq!("{3, 5}".parse::<NSRange>().unwrap(), NSRange::new(3, 5));
+            assert_eq!("3 5".parse::<NSRange>().unwrap(), NSRange::new(3, 5));
+            assert_eq!("{7}".parse::<NSRange>().unwrap(), NSRange::new(7, 0));
+            assert_eq!("none".parse::<NSRange>().unwrap(), NSRange::new(0, 0));
+            assert_eq!("{-1, 2}".parse::<NSRange>().unwrap(), NSRange::new(1, 2));
+            assert_eq!("{3, -4}".parse::<NSRange>().unwrap(), NSRange::new(3, 4));
+            assert_eq!("{99999999999999999999999, 1}".parse::<NSRange>().unwrap(),
+                       NSRange::new(!0, 1));
+        }
+
+        #[test]
+        fn test_std_range() {
+            assert_range(NSRange::from(3..8), 3, 5);
+            assert_range(NSRange::from(4..4), 4, 0);
+            assert_range(NSRange::from(Range { start: 5, end: 2 }), 5, 0);
+            let range: Range<usize> = NSRange::new(3, 5).into();
+            assert!(range == (3..8));
+        }
+
+        #[test]
+        fn test_utf8_range_ascii() {
+            let s = "Hello World";
+            assert_range(NSRange::from_utf8_range(s, 6..11).unwrap(), 6, 5);
+            assert!(NSRange::new(6, 5).to_utf8_range(s) == Some(6..11));
+            assert!(NSRange::new(11, 0).to_utf8_range(s) == Some(11..11));
+            assert!(NSRange::new(6, 6).to_utf8_range(s).is_none());
+            assert!(NSRange::from_utf8_range(s, 6..12).is_none());
+        }
+
+        #[test]
+        fn test_utf8_range_multibyte() {
+            // 'ñ' is two bytes and one UTF-16 unit, '€' is three bytes and one unit.
+            let s = "añb€c";
+            assert_range(NSRange::from_utf8_range(s, 1..3).unwrap(), 1, 1);
+            assert_range(NSRange::from_utf8_range(s, 3..7).unwrap(), 2, 2);
+            assert!(NSRange::new(2, 2).to_utf8_range(s) == Some(3..7));
+            assert!(NSRange::new(4, 1).to_utf8_range(s) == Some(7..8));
+            assert!(NSRange::from_utf8_range(s, 2..3).is_none());
+        }
+
+        #[test]
+        fn test_utf8_range_surrogate_pairs() {
+            // U+1F600 is four bytes and a surrogate pair of two UTF-16 units.
+            let s = "a\u{1F600}b";
+            assert_range(NSRange::from_utf8_range(s, 1..5).unwrap(), 1, 2);
+            assert_range(NSRange::from_utf8_range(s, 5..6).unwrap(), 3, 1);
+            assert!(NSRange::new(1, 2).to_utf8_range(s) == Some(1..5));
+            assert!(NSRange::new(3, 1).to_utf8_range(s) == Some(5..6));
+            assert!(NSRange::new(2, 1).to_utf8_range(s).is_none());
+            assert!(NSRange::new(1, 1).to_utf8_range(s).is_none());
+        }
+
+        #[test]
+        fn test_utf8_range_round_trip() {
+            let s = "Iñtërnâtiônàlizætiøn \u{1F600}!";
+            for (start, _) in s.char_indices() {
+                for (end, _) in s[start..].char_indices() {
+                    let range = NSRange::from_utf8_range(s, start..start + end).unwrap();
+                    assert!(range.to_utf8_range(s) == Some(start..start + end));
+                }
+            }
+        }
+    }
+
+    mod nsedgeinsets {
+        use cocoa::foundation::{NSEdgeInsets, NSPoint, NSSize, NSRect};
+
+        #[test]
+        fn test_inset_rect() {
+            let insets = NSEdgeInsets::new(1., 2., 3., 4.);
+            let r = NSRect::new(NSPoint::new(10., 10.), NSSize::new(100., 50.));
+            assert_eq!(insets.inset_rect(r, false),
+                       NSRect::new(NSPoint::new(12., 13.), NSSize::new(94., 46.)));
+            assert_eq!(insets.inset_rect(r, true),
+                       NSRect::new(NSPoint::new(12., 11.), NSSize::new(94., 46.)));
+            assert_eq!(NSEdgeInsets::zero().inset_rect(r, false), r);
+        }
+    }
+
+    mod nsaffinetransform {
+        use cocoa::foundation::{NSAffineTransformStruct, NSPoint, NSSize, NSRect};
+
+        fn assert_near(a: NSPoint, b: NSPoint) {
+            assert!((a.x - b.x).abs() < 1e-5 && (a.y - b.y).abs() < 1e-5,
+                    "{:?} is not close to {:?}", a, b);
+        }
+
+        #[test]
+        fn test_identity() {
+            let identity = NSAffineTransformStruct::identity();
+            assert!(identity.is_identity());
+            assert_eq!(identity.transform_point(NSPoint::new(3., 4.)), NSPoint::new(3., 4.));
+            assert!(!NSAffineTransformStruct::translation(1., 0.).is_identity());
+        }
+
+        #[test]
+        fn test_builders() {
+            let p = NSPoint::new(3., 4.);
+            assert_eq!(NSAffineTransformStruct::translation(10., 20.).transform_point(p),
+                       NSPoint::new(13., 24.));
+            assert_eq!(NSAffineTransformStruct::scale(2., -1.).transform_point(p),
+                       NSPoint::new(6., -4.));
+            assert_near(NSAffineTransformStruct::rotation_degrees(90.).transform_point(p),
+                        NSPoint::new(-4., 3.));
+        }
+
+        #[test]
+        fn test_append_and_prepend() {
+            let translate = NSAffineTransformStruct::translation(10., 0.);
+            let scale = NSAffineTransformStruct::scale(2., 2.);
+            let p = NSPoint::new(1., 1.);
+            // Translate first, then scale.
+            assert_eq!(translate.append(&scale).transform_point(p), NSPoint::new(22., 2.));
+            // Scale first, then translate.
+            assert_eq!(translate.prepend(&scale).transform_point(p), NSPoint::new(12., 2.));
+        }
+
+        #[test]
+        fn test_mutation_order() {
+            // Like NSAffineTransform, each call applies before the existing transform.
+            let t = NSAffineTransformStruct::identity().translate_by(10., 0.).scale_by(2., 2.);
+            assert_eq!(t.transform_point(NSPoint::new(1., 1.)), NSPoint::new(12., 2.));
+            let r = NSAffineTransformStruct::identity().translate_by(5., 0.).rotate_by_degrees(90.);
+            assert_near(r.transform_point(NSPoint::new(1., 0.)), NSPoint::new(5., 1.));
+        }
+
+        #[test]
+        fn test_invert() {
+            let t = NSAffineTransformStruct::identity()
+                .translate_by(3., -7.)
+                .rotate_by(0.3)
+                .scale_by(2., 0.5);
+            let inverse = t.invert().unwrap();
+            let p = NSPoint::new(12.5, -3.25);
+            assert_near(inverse.transform_point(t.transform_point(p)), p);
+            assert_near(t.append(&inverse).transform_point(p), p);
+            assert!(NSAffineTransformStruct::scale(0., 1.).invert().is_none());
+        }
+
+        #[test]
+        fn test_transform_size_and_rect() {
+            let t = NSAffineTransformStruct::translation(5., 5.).scale_by(2., 3.);
+            assert_eq!(t.transform_size(NSSize::new(10., 10.)), NSSize::new(20., 30.));
+            let r = NSRect::new(NSPoint::new(1., 1.), NSSize::new(2., 2.));
+            assert_eq!(t.transform_rect(r), NSRect::new(NSPoint::new(7., 8.), NSSize::new(4., 6.)));
+
+            let flip = NSAffineTransformStruct::scale(1., -1.);
+            assert_eq!(flip.transform_size(NSSize::new(2., 3.)), NSSize::new(2., -3.));
+            assert_eq!(flip.transform_rect(r), NSRect::new(NSPoint::new(1., -3.), NSSize::new(2., 2.)));
+        }
+    }
+
+    mod geometry_abi {
+        use cocoa::foundation::{CGFloat, NSPoint, NSSize, NSRect, NSRange, NSRectEdge,
+                                NSEdgeInsets, NSAffineTransformStruct, NSUInteger};
+        use objc::Encode;
+        use std::mem;
+
+        #[test]
+        fn test_layout() {
+            let float = mem::size_of::<CGFloat>();
+            assert_eq!(mem::size_of::<NSPoint>(), 2 * float);
+            assert_eq!(mem::size_of::<NSSize>(), 2 * float);
+            assert_eq!(mem::size_of::<NSRect>(), 4 * float);
+            assert_eq!(mem::size_of::<NSEdgeInsets>(), 4 * float);
+            assert_eq!(mem::size_of::<NSAffineTransformStruct>(), 6 * float);
+            assert_eq!(mem::align_of::<NSRect>(), mem::align_of::<CGFloat>());
+            assert_eq!(mem::size_of::<NSRange>(), 2 * mem::size_of::<NSUInteger>());
+            assert_eq!(mem::size_of::<NSRectEdge>(), mem::size_of::<NSUInteger>());
+        }
+
+        #[cfg(target_pointer_width = "64")]
+        #[test]
+        fn test_encoding() {
+            assert_eq!(mem::size_of::<CGFloat>(), 8);
+            assert_eq!(NSPoint::encode().as_str(), "{CGPoint=dd}");
+            assert_eq!(NSSize::encode().as_str(), "{CGSize=dd}");
+            assert_eq!(NSRect::encode().as_str(), "{CGRect={CGPoint=dd}{CGSize=dd}}");
+            assert_eq!(NSEdgeInsets::encode().as_str(), "{NSEdgeInsets=dddd}");
+            assert_eq!(NSAffineTransformStruct::encode().as_str(),
+                       "{_NSAffineTransformStruct=dddddd}");
+            assert_eq!(NSRange::encode().as_str(), "{_NSRange=QQ}");
+            assert_eq!(NSRectEdge::encode().as_str(), "Q");
+        }
+
+        #[cfg(target_pointer_width = "32")]
+        #[test]
+        fn test_encoding() {
+            assert_eq!(mem::size_of::<CGFloat>(), 4);
+            assert_eq!(NSPoint::encode().as_str(), "{_NSPoint=ff}");
+            assert_eq!(NSSize::encode().as_str(), "{_NSSize=ff}");
+            assert_eq!(NSRect::encode().as_str(), "{_NSRect={_NSPoint=ff}{_NSSize=ff}}");
+            assert_eq!(NSEdgeInsets::encode().as_str(), "{NSEdgeInsets=ffff}");
+            assert_eq!(NSAffineTransformStruct::encode().as_str(),
+                       "{_NSAffineTransformStruct=ffffff}");
+            assert_eq!(NSRange::encode().as_str(), "{_NSRange=II}");
+            assert_eq!(NSRectEdge::encode().as_str(), "I");
+        }
     }
 }
@@ -0,0 +1,85 @@
+extern crate cocoa;
+extern crate objc;
+
+#[cfg(test)]
+mod object {
+    use cocoa::appkit::{NSButtonRef, NSOpenGLViewRef, NSResponderRef, NSViewRef, NSWindowRef};
+    use cocoa::base::{id, nil, YES};
+    use cocoa::foundation::{NSObjectRef, NSStringRef};
+    use cocoa::object::{AsId, IsKindOf, ObjCClass};
+    use objc::Encode;
+
+    // A fake object pointer; these tests never send it a message.
+    fn fake() -> id {
+        0x1000 as id
+    }
+
+    fn is_kind_of<T: IsKindOf<S>, S: ObjCClass>() {}
+
+    #[test]
+    fn test_round_trip() {
+        let view = unsafe { NSOpenGLViewRef::from_id(fake()) };
+        assert_eq!(view.as_id(), fake());
+        assert_eq!(id::from(view), fake());
+        assert!(!view.is_nil());
+        assert!(NSOpenGLViewRef::nil().is_nil());
+        assert_eq!(unsafe { NSOpenGLViewRef::from_id(fake()) }, view);
+    }
+
+    #[test]
+    fn test_upcast() {
+        let view = unsafe { NSOpenGLViewRef::from_id(fake()) };
+        let superview: NSViewRef = view.upcast();
+        assert_eq!(superview.as_id(), fake());
+        assert_eq!(superview.upcast::<NSResponderRef>().as_id(), fake());
+        assert_eq!(view.upcast::<NSObjectRef>().as_id(), fake());
+    }
+
+    #[test]
+    fn test_downcast_nil() {
+        unsafe {
+            assert_eq!(NSWindowRef::downcast(nil), None);
+            assert_eq!(NSViewRef::downcast(NSObjectRef::nil()), None);
+        }
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        is_kind_of::<NSOpenGLViewRef, NSOpenGLViewRef>();
+        is_kind_of::<NSOpenGLViewRef, NSViewRef>();
+        is_kind_of::<NSOpenGLViewRef, NSResponderRef>();
+        is_kind_of::<NSButtonRef, NSViewRef>();
+        is_kind_of::<NSWindowRef, NSResponderRef>();
+        is_kind_of::<NSStringRef, NSObjectRef>();
+        is_kind_of::<id, NSWindowRef>();
+    }
+
+    #[test]
+    fn test_class_names() {
+        assert_eq!(NSWindowRef::class_name(), "NSWindow");
+        assert_eq!(NSOpenGLViewRef::class_name(), "NSOpenGLView");
+        assert_eq!(NSObjectRef::class_name(), "NSObject");
+    }
+
+    #[test]
+    fn test_encoding() {
+        assert_eq!(NSWindowRef::encode().as_str(), "@");
+        assert_eq!(NSStringRef::encode().as_str(), id::encode().as_str());
+    }
+
+    // The traits of a class and its superclasses are implemented for its handle. This
+    // only needs to compile.
+    #[allow(dead_code)]
+    unsafe fn use_traits(view: NSOpenGLViewRef, window: NSWindowRef, title: NSStringRef) {
+        use cocoa::appkit::{NSOpenGLView, NSView, NSWindow};
+        use cocoa::foundation::NSString;
+
+        NSOpenGLView::display_(view);
+        let _ = view.frame();
+        view.setWantsLayer(YES);
+        window.setContentView_(view.as_id());
+        window.setTitle_(title.as_id());
+        let _ = title.UTF8String();
+        let _: NSStringRef = NSStringRef::from_id(NSString::alloc(nil)).init_str("title");
+    }
+}
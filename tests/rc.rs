@@ -0,0 +1,387 @@
+extern crate cocoa;
+extern crate libc;
+
+#[cfg(test)]
+mod rc {
+    use cocoa::base::{id, nil};
+    use cocoa::rc::{AutoreleasePool, ReferenceCounting, Retained, StrongPtr, WeakPtr};
+    use libc::c_void;
+    use std::cell::{Cell, RefCell};
+    use std::panic::{self, AssertUnwindSafe};
+
+    // A stand-in object whose reference counts the `Counting` implementation keeps.
+    struct Counts {
+        strong: Cell<isize>,
+        weak: Cell<isize>,
+        autoreleased: Cell<isize>,
+        deallocated: Cell<bool>,
+    }
+
+    impl Counts {
+        // A freshly allocated object, with a retain count of one.
+        fn new() -> Counts {
+            Counts {
+                strong: Cell::new(1),
+                weak: Cell::new(0),
+                autoreleased: Cell::new(0),
+                deallocated: Cell::new(false),
+            }
+        }
+
+        fn as_id(&self) -> id {
+            self as *const Counts as id
+        }
+    }
+
+    unsafe fn counts<'a>(obj: id) -> &'a Counts {
+        &*(obj as *const Counts)
+    }
+
+    // The objects autoreleased into each pool, innermost last.
+    thread_local!(static POOLS: RefCell<Vec<Vec<id>>> = const { RefCell::new(Vec::new()) });
+
+    enum Counting {}
+
+    impl ReferenceCounting for Counting {
+        unsafe fn retain(obj: id) -> id {
+            if !obj.is_null() {
+                let counts = counts(obj);
+                assert!(!counts.deallocated.get(), "retained a deallocated object");
+                counts.strong.set(counts.strong.get() + 1);
+            }
+            obj
+        }
+
+        unsafe fn release(obj: id) {
+            if !obj.is_null() {
+                let counts = counts(obj);
+                assert!(counts.strong.get() > 0, "over-released an object");
+                counts.strong.set(counts.strong.get() - 1);
+                if counts.strong.get() == 0 {
+                    counts.deallocated.set(true);
+                }
+            }
+        }
+
+        unsafe fn autorelease(obj: id) -> id {
+            if !obj.is_null() {
+                let counts = counts(obj);
+                counts.autoreleased.set(counts.autoreleased.get() + 1);
+                POOLS.with(|pools| {
+                    if let Some(pool) = pools.borrow_mut().last_mut() {
+                        pool.push(obj);
+                    }
+                });
+            }
+            obj
+        }
+
+        unsafe fn init_weak(location: *mut id, obj: id) -> id {
+            if obj.is_null() || counts(obj).deallocated.get() {
+                *location = nil;
+            } else {
+                let counts = counts(obj);
+                counts.weak.set(counts.weak.get() + 1);
+                *location = obj;
+            }
+            *location
+        }
+
+        unsafe fn load_weak_retained(location: *mut id) -> id {
+            let obj = *location;
+            if obj.is_null() || counts(obj).deallocated.get() {
+                nil
+            } else {
+                Counting::retain(obj)
+            }
+        }
+
+        unsafe fn copy_weak(to: *mut id, from: *mut id) {
+            Counting::init_weak(to, *from);
+        }
+
+        unsafe fn destroy_weak(location: *mut id) {
+            let obj = *location;
+            if !obj.is_null() {
+                let counts = counts(obj);
+                counts.weak.set(counts.weak.get() - 1);
+            }
+            *location = nil;
+        }
+
+        unsafe fn autorelease_pool_push() -> *mut c_void {
+            POOLS.with(|pools| {
+                let mut pools = pools.borrow_mut();
+                pools.push(Vec::new());
+                pools.len() as *mut c_void
+            })
+        }
+
+        unsafe fn autorelease_pool_pop(pool: *mut c_void) {
+            let objects = POOLS.with(|pools| {
+                let mut pools = pools.borrow_mut();
+                assert_eq!(pools.len(), pool as usize, "popped a pool out of order");
+                pools.pop().unwrap()
+            });
+            for obj in objects {
+                Counting::release(obj);
+            }
+        }
+    }
+
+    // The same operations as `Counting`, with separate autorelease pools.
+    enum OtherCounting {}
+
+    impl ReferenceCounting for OtherCounting {
+        unsafe fn retain(obj: id) -> id {
+            Counting::retain(obj)
+        }
+
+        unsafe fn release(obj: id) {
+            Counting::release(obj)
+        }
+
+        unsafe fn autorelease(obj: id) -> id {
+            Counting::autorelease(obj)
+        }
+
+        unsafe fn init_weak(location: *mut id, obj: id) -> id {
+            Counting::init_weak(location, obj)
+        }
+
+        unsafe fn load_weak_retained(location: *mut id) -> id {
+            Counting::load_weak_retained(location)
+        }
+
+        unsafe fn copy_weak(to: *mut id, from: *mut id) {
+            Counting::copy_weak(to, from)
+        }
+
+        unsafe fn destroy_weak(location: *mut id) {
+            Counting::destroy_weak(location)
+        }
+
+        unsafe fn autorelease_pool_push() -> *mut c_void {
+            Counting::autorelease_pool_push()
+        }
+
+        unsafe fn autorelease_pool_pop(pool: *mut c_void) {
+            Counting::autorelease_pool_pop(pool)
+        }
+    }
+
+    #[test]
+    fn test_new_takes_ownership() {
+        let counts = Counts::new();
+        {
+            let ptr = unsafe { StrongPtr::<Counting>::from_owned(counts.as_id()) };
+            assert_eq!(*ptr, counts.as_id());
+            assert_eq!(counts.strong.get(), 1);
+        }
+        assert_eq!(counts.strong.get(), 0);
+        assert!(counts.deallocated.get());
+    }
+
+    #[test]
+    fn test_retain_unowned() {
+        let counts = Counts::new();
+        {
+            let _ptr = unsafe { StrongPtr::<Counting>::from_unowned(counts.as_id()) };
+            assert_eq!(counts.strong.get(), 2);
+        }
+        assert_eq!(counts.strong.get(), 1);
+        assert!(!counts.deallocated.get());
+    }
+
+    #[test]
+    fn test_clone() {
+        let counts = Counts::new();
+        let ptr = unsafe { StrongPtr::<Counting>::from_owned(counts.as_id()) };
+        let other = ptr.clone();
+        assert_eq!(counts.strong.get(), 2);
+        assert_eq!(*other, *ptr);
+        drop(ptr);
+        assert_eq!(counts.strong.get(), 1);
+        drop(other);
+        assert!(counts.deallocated.get());
+    }
+
+    #[test]
+    fn test_into_raw_and_autorelease() {
+        let counts = Counts::new();
+        let ptr = unsafe { StrongPtr::<Counting>::from_owned(counts.as_id()) };
+        let raw = ptr.into_raw();
+        assert_eq!(raw, counts.as_id());
+        assert_eq!(counts.strong.get(), 1);
+
+        let ptr = unsafe { StrongPtr::<Counting>::from_owned(raw) };
+        assert_eq!(ptr.autorelease(), counts.as_id());
+        assert_eq!(counts.strong.get(), 1);
+        assert_eq!(counts.autoreleased.get(), 1);
+    }
+
+    #[test]
+    fn test_weak() {
+        let counts = Counts::new();
+        let ptr = unsafe { StrongPtr::<Counting>::from_owned(counts.as_id()) };
+        let weak = ptr.weak();
+        assert_eq!(counts.weak.get(), 1);
+        assert_eq!(counts.strong.get(), 1);
+        {
+            let loaded = weak.load();
+            assert_eq!(*loaded, counts.as_id());
+            assert_eq!(counts.strong.get(), 2);
+        }
+        assert_eq!(counts.strong.get(), 1);
+
+        drop(ptr);
+        assert!(counts.deallocated.get());
+        assert!(weak.load().is_null());
+        drop(weak);
+        assert_eq!(counts.weak.get(), 0);
+    }
+
+    #[test]
+    fn test_weak_clone() {
+        let counts = Counts::new();
+        let ptr = unsafe { StrongPtr::<Counting>::from_owned(counts.as_id()) };
+        let weak = ptr.weak();
+        let other = weak.clone();
+        assert_eq!(counts.weak.get(), 2);
+        drop(weak);
+        assert_eq!(*other.load(), counts.as_id());
+        drop(other);
+        assert_eq!(counts.weak.get(), 0);
+        assert_eq!(counts.strong.get(), 1);
+    }
+
+    #[test]
+    fn test_nil() {
+        let ptr = unsafe { StrongPtr::<Counting>::from_owned(nil) };
+        assert!(ptr.is_null());
+        assert!(ptr.clone().is_null());
+        let weak = ptr.weak();
+        assert!(weak.load().is_null());
+    }
+
+    #[test]
+    fn test_retained() {
+        let counts = Counts::new();
+        {
+            let obj = unsafe { Retained::<id, Counting>::from_owned(counts.as_id()) };
+            assert_eq!(*obj, counts.as_id());
+            let other = obj.clone();
+            assert_eq!(counts.strong.get(), 2);
+            assert!(other == counts.as_id());
+        }
+        assert!(counts.deallocated.get());
+
+        let counts = Counts::new();
+        let obj = unsafe { Retained::<id, Counting>::from_unowned(counts.as_id()) };
+        assert_eq!(counts.strong.get(), 2);
+        let ptr: StrongPtr<Counting> = obj.into();
+        assert_eq!(counts.strong.get(), 2);
+        drop(ptr);
+        assert_eq!(counts.strong.get(), 1);
+
+        let obj = unsafe { Retained::<id, Counting>::from_owned(counts.as_id()) };
+        assert_eq!(obj.autorelease(), counts.as_id());
+        assert_eq!(counts.autoreleased.get(), 1);
+        assert_eq!(counts.strong.get(), 1);
+    }
+
+    #[test]
+    fn test_autoreleasepool_drains() {
+        let counts = Counts::new();
+        let value = AutoreleasePool::<Counting>::with(|pool| {
+            let obj = pool.autorelease(unsafe { StrongPtr::from_owned(counts.as_id()) });
+            assert_eq!(obj.get().map(|obj| obj as *const _ as id), Some(counts.as_id()));
+            assert_eq!(counts.strong.get(), 1);
+            42
+        });
+        assert_eq!(value, 42);
+        assert!(counts.deallocated.get());
+    }
+
+    #[test]
+    fn test_autoreleasepool_nests() {
+        let outer_counts = Counts::new();
+        let inner_counts = Counts::new();
+        AutoreleasePool::<Counting>::with(|outer| {
+            outer.autorelease(unsafe { StrongPtr::from_owned(outer_counts.as_id()) });
+            AutoreleasePool::<Counting>::with(|inner| {
+                inner.autorelease(unsafe { StrongPtr::from_owned(inner_counts.as_id()) });
+            });
+            assert!(inner_counts.deallocated.get());
+            assert!(!outer_counts.deallocated.get());
+
+            // The outer pool is the innermost one again.
+            let _ = unsafe { outer.bind(outer_counts.as_id()) };
+        });
+        assert!(outer_counts.deallocated.get());
+    }
+
+    #[test]
+    fn test_autoreleasepool_depth_is_per_implementation() {
+        AutoreleasePool::<Counting>::with(|outer| {
+            AutoreleasePool::<OtherCounting>::with(|inner| {
+                // Each pool is the innermost one of its own implementation.
+                assert!(unsafe { inner.bind(nil) }.get().is_none());
+                assert!(unsafe { outer.bind(nil) }.get().is_none());
+            });
+        });
+    }
+
+    #[test]
+    fn test_autoreleasepool_drains_on_panic() {
+        let counts = Counts::new();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            AutoreleasePool::<Counting>::with(|pool| {
+                pool.autorelease(unsafe { StrongPtr::from_owned(counts.as_id()) });
+                panic!("unwinding through the pool");
+            })
+        }));
+        assert!(result.is_err());
+        assert!(counts.deallocated.get());
+
+        // The pool depth was restored, so new pools still work.
+        AutoreleasePool::<Counting>::with(|pool| {
+            assert!(unsafe { pool.bind(nil) }.get().is_none());
+        });
+    }
+
+    #[test]
+    fn test_retain_outlives_pool() {
+        let counts = Counts::new();
+        let kept = AutoreleasePool::<Counting>::with(|pool| {
+            pool.autorelease(unsafe { StrongPtr::from_owned(counts.as_id()) }).retain()
+        });
+        assert_eq!(*kept, counts.as_id());
+        assert_eq!(counts.strong.get(), 1);
+        assert!(!counts.deallocated.get());
+    }
+
+    #[test]
+    #[should_panic(expected = "innermost pool")]
+    fn test_autorelease_into_outer_pool() {
+        let counts = Counts::new();
+        AutoreleasePool::<Counting>::with(|outer| {
+            AutoreleasePool::<Counting>::with(|_| {
+                outer.autorelease(unsafe { StrongPtr::from_owned(counts.as_id()) });
+            });
+        });
+    }
+
+    // The foundation and appkit traits are implemented for `id`, and are reached through
+    // `Deref`. This only needs to compile.
+    #[allow(dead_code)]
+    unsafe fn use_traits(window: &StrongPtr, title: &StrongPtr) {
+        use cocoa::appkit::NSWindow;
+        use cocoa::foundation::NSString;
+
+        window.setTitle_(**title);
+        window.makeKeyAndOrderFront_(nil);
+        let _ = title.UTF8String();
+        let _: WeakPtr = window.weak();
+    }
+}